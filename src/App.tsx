@@ -1,5 +1,5 @@
 import React, { useEffect, useRef } from "react";
-import { Renderer } from "../wasm/pkg/wasm";
+import { Camera, Path, Renderer } from "../wasm/pkg/wasm";
 
 function App() {
   const containerRef = React.createRef<HTMLDivElement>();
@@ -8,19 +8,96 @@ function App() {
   useEffect(() => {
     if (containerRef.current) {
       const canvas = document.createElement("canvas");
-      canvas.width = 1000;
-      canvas.height = 1000;
-      canvas.style.width = "500px";
-      canvas.style.height = "500px";
-      canvas.style.imageRendering = "pixelated";
+      canvas.style.width = "100%";
+      canvas.style.height = "100%";
+      canvas.style.display = "block";
       containerRef.current.append(canvas);
+
+      // the canvas is laid out in CSS pixels and rendered at the device's resolution
+      const resize = () => {
+        const renderer = rendererRef.current;
+        if (!renderer) {
+          return;
+        }
+        const rect = canvas.getBoundingClientRect();
+        const width = Math.round(rect.width);
+        const height = Math.round(rect.height);
+        const ratio = window.devicePixelRatio;
+        canvas.width = Math.max(1, Math.round(width * ratio));
+        canvas.height = Math.max(1, Math.round(height * ratio));
+        renderer.resize(width, height, ratio);
+        renderer.render();
+      };
+      const observer = new ResizeObserver(resize);
+
+      // the camera works in CSS pixels relative to the canvas
+      const updateCamera = (update: (camera: Camera) => void) => {
+        const renderer = rendererRef.current;
+        if (!renderer) {
+          return;
+        }
+        const camera = renderer.camera;
+        update(camera);
+        renderer.setCamera(camera);
+        camera.free();
+        renderer.render();
+      };
+
+      const onWheel = (event: WheelEvent) => {
+        event.preventDefault();
+        const rect = canvas.getBoundingClientRect();
+        updateCamera((camera) =>
+          camera.zoomAt(
+            event.clientX - rect.left,
+            event.clientY - rect.top,
+            Math.exp(-event.deltaY * 0.002)
+          )
+        );
+      };
+
+      const onPointerDown = (event: PointerEvent) => {
+        canvas.setPointerCapture(event.pointerId);
+      };
+      const onPointerMove = (event: PointerEvent) => {
+        if (canvas.hasPointerCapture(event.pointerId)) {
+          updateCamera((camera) => camera.pan(event.movementX, event.movementY));
+        }
+      };
+
+      canvas.addEventListener("wheel", onWheel, { passive: false });
+      canvas.addEventListener("pointerdown", onPointerDown);
+      canvas.addEventListener("pointermove", onPointerMove);
+
       Renderer.new(canvas).then((renderer) => {
         // TODO: cleanup renderer
         rendererRef.current = renderer;
-        renderer.render();
+
+        const heart = Path.fromSvg(
+          "M64 200 a40 40 0 0 1 80 0 a40 40 0 0 1 80 0 q0 60 -80 120 q-80 -60 -80 -120 z"
+        );
+        renderer.addPath(heart, 0.86, 0.15, 0.15, 1);
+        heart.free();
+
+        const triangle = new Path();
+        triangle.moveTo(280, 320);
+        triangle.lineTo(360, 180);
+        triangle.lineTo(440, 320);
+        triangle.close();
+        renderer.addPath(triangle, 0.09, 0.64, 0.29, 1);
+        triangle.free();
+
+        observer.observe(canvas);
+        // devicePixelRatio changes when the window moves to another screen or on zoom
+        window.addEventListener("resize", resize);
+        resize();
       });
 
       return () => {
+        observer.disconnect();
+        canvas.removeEventListener("wheel", onWheel);
+        canvas.removeEventListener("pointerdown", onPointerDown);
+        canvas.removeEventListener("pointermove", onPointerMove);
+        window.removeEventListener("resize", resize);
         canvas.remove();
       };
     }
@@ -28,7 +105,7 @@ function App() {
 
   return (
     <div className="p-4">
-      <div className="w-fit border border-black" ref={containerRef} />
+      <div className="h-[500px] w-[500px] border border-black" ref={containerRef} />
     </div>
   );
 }
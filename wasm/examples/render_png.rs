@@ -0,0 +1,96 @@
+// Renders the example scene natively without a window and saves it as a PNG:
+//
+//     cargo run --example render_png -- out.png 800 600
+
+use std::env;
+
+use wasm::{
+    block_on,
+    renderer::{Renderer, Scene},
+};
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let path = args.next().unwrap_or_else(|| "render.png".to_string());
+    let width = args.next().map_or(800, |width| width.parse().unwrap());
+    let height = args.next().map_or(600, |height| height.parse().unwrap());
+
+    let pixels = block_on(async {
+        let mut renderer =
+            Renderer::new_headless(width, height, wgpu::TextureFormat::Rgba8Unorm).await;
+        for (_, shape) in Scene::example(width, height).shapes() {
+            renderer.add_shape(shape.clone());
+        }
+        renderer.render();
+        renderer.read_pixels().await.unwrap()
+    });
+
+    std::fs::write(&path, encode_png(width, height, &pixels)).unwrap();
+    println!("wrote {}x{} pixels to {}", width, height, path);
+}
+
+// Minimal PNG encoder (8-bit RGBA, uncompressed deflate blocks) to avoid extra dependencies
+fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    // every row starts with filter type 0 (none)
+    let mut raw = Vec::with_capacity(rgba.len() + height as usize);
+    for row in rgba.chunks(width as usize * 4) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let blocks: Vec<&[u8]> = raw.chunks(u16::MAX as usize).collect();
+    for (i, block) in blocks.iter().enumerate() {
+        zlib.push((i + 1 == blocks.len()) as u8);
+        let length = block.len() as u16;
+        zlib.extend_from_slice(&length.to_le_bytes());
+        zlib.extend_from_slice(&(!length).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::new();
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // bit depth 8, color type RGBA, default compression, filtering and no interlacing
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut png = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib);
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
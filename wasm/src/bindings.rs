@@ -0,0 +1,286 @@
+// JavaScript API for building paths and shapes at runtime.
+// `///` comments end up in the generated TypeScript typings.
+
+use cgmath::{point2, vec2, vec4};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    geometry::{FillRule, Path},
+    renderer::{BlendMode, Camera, Renderer, Shape, ShapeId},
+};
+
+/// A path in scene coordinates (CSS pixels, y down), filled with `fillRule` when drawn.
+#[wasm_bindgen(js_name = Path)]
+#[derive(Clone, Debug, Default)]
+pub struct JsPath {
+    path: Path,
+    fill_rule: FillRule,
+}
+
+#[wasm_bindgen(js_class = Path)]
+impl JsPath {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> JsPath {
+        JsPath::default()
+    }
+
+    /// Parses SVG path data, e.g. the `d` attribute of a `<path>` element.
+    #[wasm_bindgen(js_name = fromSvg)]
+    pub fn from_svg(data: &str) -> Result<JsPath, JsError> {
+        Ok(JsPath {
+            path: Path::from_svg(data).map_err(|error| JsError::new(&error.to_string()))?,
+            fill_rule: FillRule::default(),
+        })
+    }
+
+    /// Decodes a path from one verb per command (0 move, 1 line, 2 quad, 3 cubic, 4 close)
+    /// and the x, y coordinates of the commands' points, e.g. a `Uint8Array` and a `Float32Array`.
+    #[wasm_bindgen(js_name = fromBuffers)]
+    pub fn from_buffers(verbs: &[u8], coordinates: &[f32]) -> Result<JsPath, JsError> {
+        Ok(JsPath {
+            path: Path::decode(verbs, coordinates)
+                .map_err(|error| JsError::new(&error.to_string()))?,
+            fill_rule: FillRule::default(),
+        })
+    }
+
+    #[wasm_bindgen(js_name = moveTo)]
+    pub fn move_to(&mut self, x: f64, y: f64) {
+        self.path.move_to(point2(x, y));
+    }
+
+    #[wasm_bindgen(js_name = lineTo)]
+    pub fn line_to(&mut self, x: f64, y: f64) {
+        self.path.line_to(point2(x, y));
+    }
+
+    #[wasm_bindgen(js_name = quadTo)]
+    pub fn quad_to(&mut self, cx: f64, cy: f64, x: f64, y: f64) {
+        self.path.quad_to(point2(cx, cy), point2(x, y));
+    }
+
+    #[wasm_bindgen(js_name = cubicTo)]
+    pub fn cubic_to(&mut self, c1x: f64, c1y: f64, c2x: f64, c2y: f64, x: f64, y: f64) {
+        self.path
+            .cubic_to(point2(c1x, c1y), point2(c2x, c2y), point2(x, y));
+    }
+
+    pub fn close(&mut self) {
+        self.path.close();
+    }
+
+    #[wasm_bindgen(js_name = horizontalLineTo)]
+    pub fn horizontal_line_to(&mut self, x: f64) {
+        self.path.horizontal_line_to(x);
+    }
+
+    #[wasm_bindgen(js_name = verticalLineTo)]
+    pub fn vertical_line_to(&mut self, y: f64) {
+        self.path.vertical_line_to(y);
+    }
+
+    /// Quadratic curve whose control point mirrors the previous one, like SVG's `T` command.
+    #[wasm_bindgen(js_name = smoothQuadTo)]
+    pub fn smooth_quad_to(&mut self, x: f64, y: f64) {
+        self.path.smooth_quad_to(point2(x, y));
+    }
+
+    /// Cubic curve whose first control point mirrors the previous one, like SVG's `S` command.
+    #[wasm_bindgen(js_name = smoothCubicTo)]
+    pub fn smooth_cubic_to(&mut self, c2x: f64, c2y: f64, x: f64, y: f64) {
+        self.path.smooth_cubic_to(point2(c2x, c2y), point2(x, y));
+    }
+
+    /// Elliptical arc like SVG's `A` command, with `xAxisRotation` in degrees.
+    #[wasm_bindgen(js_name = arcTo)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn arc_to(
+        &mut self,
+        rx: f64,
+        ry: f64,
+        x_axis_rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        x: f64,
+        y: f64,
+    ) {
+        self.path.arc_to(
+            vec2(rx, ry),
+            x_axis_rotation,
+            large_arc,
+            sweep,
+            point2(x, y),
+        );
+    }
+
+    /// Moves by an offset from the current point, or from the origin at the start of the path.
+    #[wasm_bindgen(js_name = relativeMoveTo)]
+    pub fn relative_move_to(&mut self, dx: f64, dy: f64) {
+        self.path.relative_move_to(vec2(dx, dy));
+    }
+
+    #[wasm_bindgen(js_name = relativeLineTo)]
+    pub fn relative_line_to(&mut self, dx: f64, dy: f64) {
+        self.path.relative_line_to(vec2(dx, dy));
+    }
+
+    #[wasm_bindgen(js_name = relativeQuadTo)]
+    pub fn relative_quad_to(&mut self, dcx: f64, dcy: f64, dx: f64, dy: f64) {
+        self.path.relative_quad_to(vec2(dcx, dcy), vec2(dx, dy));
+    }
+
+    #[wasm_bindgen(js_name = relativeCubicTo)]
+    pub fn relative_cubic_to(
+        &mut self,
+        dc1x: f64,
+        dc1y: f64,
+        dc2x: f64,
+        dc2y: f64,
+        dx: f64,
+        dy: f64,
+    ) {
+        self.path
+            .relative_cubic_to(vec2(dc1x, dc1y), vec2(dc2x, dc2y), vec2(dx, dy));
+    }
+
+    #[wasm_bindgen(js_name = relativeHorizontalLineTo)]
+    pub fn relative_horizontal_line_to(&mut self, dx: f64) {
+        self.path.relative_horizontal_line_to(dx);
+    }
+
+    #[wasm_bindgen(js_name = relativeVerticalLineTo)]
+    pub fn relative_vertical_line_to(&mut self, dy: f64) {
+        self.path.relative_vertical_line_to(dy);
+    }
+
+    #[wasm_bindgen(js_name = relativeSmoothQuadTo)]
+    pub fn relative_smooth_quad_to(&mut self, dx: f64, dy: f64) {
+        self.path.relative_smooth_quad_to(vec2(dx, dy));
+    }
+
+    #[wasm_bindgen(js_name = relativeSmoothCubicTo)]
+    pub fn relative_smooth_cubic_to(&mut self, dc2x: f64, dc2y: f64, dx: f64, dy: f64) {
+        self.path
+            .relative_smooth_cubic_to(vec2(dc2x, dc2y), vec2(dx, dy));
+    }
+
+    #[wasm_bindgen(js_name = relativeArcTo)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn relative_arc_to(
+        &mut self,
+        rx: f64,
+        ry: f64,
+        x_axis_rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        dx: f64,
+        dy: f64,
+    ) {
+        self.path.relative_arc_to(
+            vec2(rx, ry),
+            x_axis_rotation,
+            large_arc,
+            sweep,
+            vec2(dx, dy),
+        );
+    }
+
+    #[wasm_bindgen(getter, js_name = fillRule)]
+    pub fn fill_rule(&self) -> FillRule {
+        self.fill_rule
+    }
+
+    #[wasm_bindgen(setter, js_name = fillRule)]
+    pub fn set_fill_rule(&mut self, fill_rule: FillRule) {
+        self.fill_rule = fill_rule;
+    }
+}
+
+impl JsPath {
+    // Tessellates the path into a shape with a straight-alpha RGBA color in [0, 1]
+    fn to_shape(&self, r: f32, g: f32, b: f32, a: f32) -> Result<Shape, JsError> {
+        self.path
+            .validate()
+            .map_err(|error| JsError::new(&error.to_string()))?;
+        Shape::from_path(&self.path, self.fill_rule, vec4(r, g, b, a))
+            .map_err(|error| JsError::new(&format!("failed to tessellate path: {:?}", error)))
+    }
+}
+
+#[wasm_bindgen]
+impl Renderer {
+    /// Adds the filled path on top of the other shapes and returns the new shape's id.
+    /// The color is straight-alpha RGBA with components in [0, 1].
+    #[wasm_bindgen(js_name = addPath)]
+    pub fn add_path(
+        &mut self,
+        path: &JsPath,
+        r: f32,
+        g: f32,
+        b: f32,
+        a: f32,
+    ) -> Result<u32, JsError> {
+        let shape = path.to_shape(r, g, b, a)?;
+        Ok(self.add_shape(shape).0)
+    }
+
+    /// Replaces the geometry and color of a shape, keeping its place in the drawing order.
+    /// Returns false if there is no shape with this id.
+    #[wasm_bindgen(js_name = setPath)]
+    pub fn set_path(
+        &mut self,
+        id: u32,
+        path: &JsPath,
+        r: f32,
+        g: f32,
+        b: f32,
+        a: f32,
+    ) -> Result<bool, JsError> {
+        let mut shape = path.to_shape(r, g, b, a)?;
+        match self.scene().shape(ShapeId(id)) {
+            Some(existing) => {
+                shape.transform = existing.transform;
+                shape.blend_mode = existing.blend_mode;
+            }
+            None => return Ok(false),
+        }
+        Ok(self.update_shape(ShapeId(id), shape))
+    }
+
+    /// Changes the fill color of a shape (straight-alpha RGBA in [0, 1]) without rebuilding it.
+    /// Returns false if there is no shape with this id.
+    #[wasm_bindgen(js_name = setFillColor)]
+    pub fn js_set_fill_color(&mut self, id: u32, r: f32, g: f32, b: f32, a: f32) -> bool {
+        self.set_fill_color(ShapeId(id), vec4(r, g, b, a))
+    }
+
+    /// Changes how a shape mixes with the shapes beneath it, e.g. `BlendMode.Multiply`.
+    /// Returns false if there is no shape with this id.
+    #[wasm_bindgen(js_name = setBlendMode)]
+    pub fn js_set_blend_mode(&mut self, id: u32, blend_mode: BlendMode) -> bool {
+        self.set_blend_mode(ShapeId(id), blend_mode)
+    }
+
+    /// Returns false if there is no shape with this id.
+    #[wasm_bindgen(js_name = removeShape)]
+    pub fn js_remove_shape(&mut self, id: u32) -> bool {
+        self.remove_shape(ShapeId(id))
+    }
+}
+
+#[wasm_bindgen]
+impl Camera {
+    /// Converts a point from CSS pixels relative to the canvas to scene coordinates, as `[x, y]`.
+    #[wasm_bindgen(js_name = screenToWorld)]
+    pub fn js_screen_to_world(&self, x: f64, y: f64) -> Vec<f64> {
+        let point = self.screen_to_world(point2(x, y));
+        vec![point.x, point.y]
+    }
+
+    /// Converts a point from scene coordinates to CSS pixels relative to the canvas, as `[x, y]`.
+    #[wasm_bindgen(js_name = worldToScreen)]
+    pub fn js_world_to_screen(&self, x: f64, y: f64) -> Vec<f64> {
+        let point = self.world_to_screen(point2(x, y));
+        vec![point.x, point.y]
+    }
+}
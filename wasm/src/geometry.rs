@@ -1,3 +1,49 @@
+mod builder;
+mod circle;
+mod clip;
+mod corners;
+mod distance;
+mod encoding;
+mod error;
+mod fill_rule;
+mod fitting;
+mod hull;
+mod monotonic;
+mod offset;
+mod oriented_rect;
 mod path;
+mod polynomial;
+mod quadratics;
+mod rect;
+mod sdf;
 mod segment;
+mod self_intersection;
+mod spatial_index;
+mod spline;
+// kept close to the Anti-Grain Geometry original, including its names
+#[allow(non_snake_case, non_upper_case_globals, dead_code, clippy::all)]
 mod subdivision;
+mod svg;
+mod trace;
+mod warp;
+
+pub use builder::PathBuilder;
+pub use circle::Circle;
+pub use encoding::{
+    Coordinate, DecodeError, EncodedPath, VERB_CLOSE, VERB_CUBIC_TO, VERB_LINE_TO, VERB_MOVE_TO,
+    VERB_QUAD_TO,
+};
+pub use error::PathError;
+pub use fill_rule::FillRule;
+pub use hull::ConvexPolygon;
+pub use monotonic::MonotoneEdge;
+pub use offset::{LineJoin, OffsetOptions};
+pub use oriented_rect::OrientedRect;
+pub use path::Path;
+pub use rect::Rect;
+pub use sdf::{generate_msdf, generate_sdf, DistanceField, SdfOptions};
+pub use spatial_index::SpatialIndex;
+pub use spline::{BSpline, CatmullRom, MonotoneCubic, Spline, SplineError};
+pub use svg::SvgError;
+pub use trace::{trace_bitmap, Bitmap, BitmapError, TraceOptions};
+pub use warp::{Envelope, MeshWarp, Perspective, Warp, WarpError};
@@ -0,0 +1,276 @@
+use cgmath::{Point2, Vector2};
+
+use super::{
+    error::PathError,
+    path::{Path, PathCommand},
+};
+
+// Path builder that rejects invalid commands instead of storing them.
+// It has the same commands as `Path`; errors refer to the index of the command that would have been added.
+#[derive(Clone, Debug, Default)]
+pub struct PathBuilder {
+    path: Path,
+    has_move_to: bool,
+}
+
+impl PathBuilder {
+    pub fn new() -> PathBuilder {
+        PathBuilder::default()
+    }
+
+    pub fn move_to(&mut self, point: Point2<f64>) -> Result<(), PathError> {
+        self.push(PathCommand::MoveTo(point))
+    }
+
+    pub fn line_to(&mut self, point: Point2<f64>) -> Result<(), PathError> {
+        self.push(PathCommand::LineTo(point))
+    }
+
+    pub fn quad_to(&mut self, control: Point2<f64>, point: Point2<f64>) -> Result<(), PathError> {
+        self.push(PathCommand::QuadTo(control, point))
+    }
+
+    pub fn cubic_to(
+        &mut self,
+        control1: Point2<f64>,
+        control2: Point2<f64>,
+        point: Point2<f64>,
+    ) -> Result<(), PathError> {
+        self.push(PathCommand::CubicTo(control1, control2, point))
+    }
+
+    pub fn close(&mut self) -> Result<(), PathError> {
+        self.push(PathCommand::Close)
+    }
+
+    pub fn horizontal_line_to(&mut self, x: f64) -> Result<(), PathError> {
+        self.check(&[x], false)?;
+        self.path.horizontal_line_to(x);
+        Ok(())
+    }
+
+    pub fn vertical_line_to(&mut self, y: f64) -> Result<(), PathError> {
+        self.check(&[y], false)?;
+        self.path.vertical_line_to(y);
+        Ok(())
+    }
+
+    pub fn smooth_quad_to(&mut self, point: Point2<f64>) -> Result<(), PathError> {
+        self.check(&[point.x, point.y], false)?;
+        self.path.smooth_quad_to(point);
+        Ok(())
+    }
+
+    pub fn smooth_cubic_to(
+        &mut self,
+        control2: Point2<f64>,
+        point: Point2<f64>,
+    ) -> Result<(), PathError> {
+        self.check(&[control2.x, control2.y, point.x, point.y], false)?;
+        self.path.smooth_cubic_to(control2, point);
+        Ok(())
+    }
+
+    pub fn arc_to(
+        &mut self,
+        radii: Vector2<f64>,
+        x_axis_rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        point: Point2<f64>,
+    ) -> Result<(), PathError> {
+        self.check(
+            &[radii.x, radii.y, x_axis_rotation, point.x, point.y],
+            false,
+        )?;
+        self.path
+            .arc_to(radii, x_axis_rotation, large_arc, sweep, point);
+        Ok(())
+    }
+
+    // A relative MoveTo may start the path, from the origin like in `Path`
+    pub fn relative_move_to(&mut self, to: Vector2<f64>) -> Result<(), PathError> {
+        self.check(&[to.x, to.y], true)?;
+        self.path.relative_move_to(to);
+        self.has_move_to = true;
+        Ok(())
+    }
+
+    pub fn relative_line_to(&mut self, to: Vector2<f64>) -> Result<(), PathError> {
+        self.check(&[to.x, to.y], false)?;
+        self.path.relative_line_to(to);
+        Ok(())
+    }
+
+    pub fn relative_quad_to(
+        &mut self,
+        control: Vector2<f64>,
+        to: Vector2<f64>,
+    ) -> Result<(), PathError> {
+        self.check(&[control.x, control.y, to.x, to.y], false)?;
+        self.path.relative_quad_to(control, to);
+        Ok(())
+    }
+
+    pub fn relative_cubic_to(
+        &mut self,
+        control1: Vector2<f64>,
+        control2: Vector2<f64>,
+        to: Vector2<f64>,
+    ) -> Result<(), PathError> {
+        self.check(
+            &[control1.x, control1.y, control2.x, control2.y, to.x, to.y],
+            false,
+        )?;
+        self.path.relative_cubic_to(control1, control2, to);
+        Ok(())
+    }
+
+    pub fn relative_horizontal_line_to(&mut self, dx: f64) -> Result<(), PathError> {
+        self.check(&[dx], false)?;
+        self.path.relative_horizontal_line_to(dx);
+        Ok(())
+    }
+
+    pub fn relative_vertical_line_to(&mut self, dy: f64) -> Result<(), PathError> {
+        self.check(&[dy], false)?;
+        self.path.relative_vertical_line_to(dy);
+        Ok(())
+    }
+
+    pub fn relative_smooth_quad_to(&mut self, to: Vector2<f64>) -> Result<(), PathError> {
+        self.check(&[to.x, to.y], false)?;
+        self.path.relative_smooth_quad_to(to);
+        Ok(())
+    }
+
+    pub fn relative_smooth_cubic_to(
+        &mut self,
+        control2: Vector2<f64>,
+        to: Vector2<f64>,
+    ) -> Result<(), PathError> {
+        self.check(&[control2.x, control2.y, to.x, to.y], false)?;
+        self.path.relative_smooth_cubic_to(control2, to);
+        Ok(())
+    }
+
+    pub fn relative_arc_to(
+        &mut self,
+        radii: Vector2<f64>,
+        x_axis_rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        to: Vector2<f64>,
+    ) -> Result<(), PathError> {
+        self.check(&[radii.x, radii.y, x_axis_rotation, to.x, to.y], false)?;
+        self.path
+            .relative_arc_to(radii, x_axis_rotation, large_arc, sweep, to);
+        Ok(())
+    }
+
+    pub fn push(&mut self, command: PathCommand) -> Result<(), PathError> {
+        command.validate(self.path.commands().len(), self.has_move_to)?;
+        self.has_move_to |= command.is_move_to();
+        self.path.push(command);
+        Ok(())
+    }
+
+    pub fn build(self) -> Path {
+        self.path
+    }
+
+    // Same checks as `PathCommand::validate`, on the arguments of commands that derive their points
+    // from the current point
+    fn check(&self, values: &[f64], is_move_to: bool) -> Result<(), PathError> {
+        let command_index = self.path.commands().len();
+        if values.iter().any(|value| !value.is_finite()) {
+            return Err(PathError::NonFiniteCoordinate { command_index });
+        }
+        if !self.has_move_to && !is_move_to {
+            return Err(PathError::MissingMoveTo { command_index });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{point2, vec2};
+
+    use super::*;
+
+    #[test]
+    fn builds_the_same_path_as_path() {
+        let mut builder = PathBuilder::new();
+        builder.relative_move_to(vec2(1.0, 1.0)).unwrap();
+        builder.horizontal_line_to(5.0).unwrap();
+        builder.relative_vertical_line_to(4.0).unwrap();
+        builder
+            .cubic_to(point2(5.0, 6.0), point2(4.0, 7.0), point2(3.0, 7.0))
+            .unwrap();
+        builder
+            .relative_smooth_cubic_to(vec2(-2.0, 0.0), vec2(-2.0, -1.0))
+            .unwrap();
+        builder
+            .relative_quad_to(vec2(0.0, -2.0), vec2(1.0, -2.0))
+            .unwrap();
+        builder.smooth_quad_to(point2(1.0, 1.0)).unwrap();
+        builder
+            .arc_to(vec2(2.0, 2.0), 0.0, false, true, point2(5.0, 1.0))
+            .unwrap();
+        builder.close().unwrap();
+
+        let mut path = Path::new();
+        path.relative_move_to(vec2(1.0, 1.0));
+        path.horizontal_line_to(5.0);
+        path.relative_vertical_line_to(4.0);
+        path.cubic_to(point2(5.0, 6.0), point2(4.0, 7.0), point2(3.0, 7.0));
+        path.relative_smooth_cubic_to(vec2(-2.0, 0.0), vec2(-2.0, -1.0));
+        path.relative_quad_to(vec2(0.0, -2.0), vec2(1.0, -2.0));
+        path.smooth_quad_to(point2(1.0, 1.0));
+        path.arc_to(vec2(2.0, 2.0), 0.0, false, true, point2(5.0, 1.0));
+        path.close();
+
+        assert_eq!(builder.build().commands(), path.commands());
+    }
+
+    #[test]
+    fn rejects_non_finite_coordinates() {
+        let mut builder = PathBuilder::new();
+        builder.move_to(point2(0.0, 0.0)).unwrap();
+        assert_eq!(
+            builder.line_to(point2(f64::NAN, 0.0)),
+            Err(PathError::NonFiniteCoordinate { command_index: 1 })
+        );
+        assert_eq!(
+            builder.relative_smooth_cubic_to(vec2(1.0, 1.0), vec2(f64::INFINITY, 0.0)),
+            Err(PathError::NonFiniteCoordinate { command_index: 1 })
+        );
+        assert_eq!(
+            builder.arc_to(vec2(1.0, 1.0), f64::NAN, false, false, point2(1.0, 0.0)),
+            Err(PathError::NonFiniteCoordinate { command_index: 1 })
+        );
+        // rejected commands are not added
+        builder.vertical_line_to(2.0).unwrap();
+        assert_eq!(builder.build().commands().len(), 2);
+    }
+
+    #[test]
+    fn rejects_commands_before_the_first_move_to() {
+        let mut builder = PathBuilder::new();
+        assert_eq!(
+            builder.line_to(point2(1.0, 1.0)),
+            Err(PathError::MissingMoveTo { command_index: 0 })
+        );
+        assert_eq!(
+            builder.relative_horizontal_line_to(1.0),
+            Err(PathError::MissingMoveTo { command_index: 0 })
+        );
+        assert_eq!(
+            builder.close(),
+            Err(PathError::MissingMoveTo { command_index: 0 })
+        );
+        builder.move_to(point2(0.0, 0.0)).unwrap();
+        builder.relative_horizontal_line_to(1.0).unwrap();
+    }
+}
@@ -0,0 +1,129 @@
+use cgmath::{point2, vec2, InnerSpace, MetricSpace, Point2};
+
+use super::path::Path;
+
+// Control point distance for approximating a quarter circle with a cubic
+const KAPPA: f64 = 0.5522847498307936;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Circle {
+    pub center: Point2<f64>,
+    pub radius: f64,
+}
+
+impl Circle {
+    pub fn new(center: Point2<f64>, radius: f64) -> Circle {
+        Circle { center, radius }
+    }
+
+    pub fn contains(&self, point: Point2<f64>) -> bool {
+        self.center.distance2(point) <= self.radius * self.radius
+    }
+
+    pub fn area(&self) -> f64 {
+        std::f64::consts::PI * self.radius * self.radius
+    }
+
+    // Smallest circle containing all the points (Welzl's algorithm)
+    pub fn enclosing(points: &[Point2<f64>]) -> Option<Circle> {
+        let mut points = points.to_vec();
+        // deterministic shuffle for the expected linear running time
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        for i in (1..points.len()).rev() {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            points.swap(i, (seed % (i as u64 + 1)) as usize);
+        }
+
+        let scale = points.iter().fold(0.0f64, |scale, point| {
+            scale.max(point.x.abs()).max(point.y.abs())
+        });
+        let epsilon = 1e-9 * scale.max(1.0);
+        let outside = |circle: &Circle, point: Point2<f64>| {
+            circle.center.distance(point) > circle.radius + epsilon
+        };
+
+        let mut circle = Circle::new(*points.first()?, 0.0);
+        for i in 1..points.len() {
+            if !outside(&circle, points[i]) {
+                continue;
+            }
+            circle = Circle::new(points[i], 0.0);
+            for j in 0..i {
+                if !outside(&circle, points[j]) {
+                    continue;
+                }
+                circle = diameter_circle(points[i], points[j]);
+                for k in 0..j {
+                    if outside(&circle, points[k]) {
+                        circle = circumcircle(points[i], points[j], points[k]);
+                    }
+                }
+            }
+        }
+        Some(circle)
+    }
+
+    // Four cubic arcs, counter-clockwise in y-up coordinates
+    pub fn to_path(&self) -> Path {
+        let (c, r) = (self.center, self.radius);
+        let k = KAPPA * r;
+        let mut path = Path::new();
+        path.move_to(point2(c.x + r, c.y));
+        path.cubic_to(
+            point2(c.x + r, c.y + k),
+            point2(c.x + k, c.y + r),
+            point2(c.x, c.y + r),
+        );
+        path.cubic_to(
+            point2(c.x - k, c.y + r),
+            point2(c.x - r, c.y + k),
+            point2(c.x - r, c.y),
+        );
+        path.cubic_to(
+            point2(c.x - r, c.y - k),
+            point2(c.x - k, c.y - r),
+            point2(c.x, c.y - r),
+        );
+        path.cubic_to(
+            point2(c.x + k, c.y - r),
+            point2(c.x + r, c.y - k),
+            point2(c.x + r, c.y),
+        );
+        path.close();
+        path
+    }
+}
+
+fn diameter_circle(a: Point2<f64>, b: Point2<f64>) -> Circle {
+    Circle::new(a + (b - a) * 0.5, a.distance(b) / 2.0)
+}
+
+fn circumcircle(a: Point2<f64>, b: Point2<f64>, c: Point2<f64>) -> Circle {
+    let (ab, ac) = (b - a, c - a);
+    let d = 2.0 * (ab.x * ac.y - ab.y * ac.x);
+    if d.abs() <= 1e-12 * ab.magnitude2().max(ac.magnitude2()) {
+        // collinear points: the circle through the two farthest apart
+        return [
+            diameter_circle(a, b),
+            diameter_circle(a, c),
+            diameter_circle(b, c),
+        ]
+        .into_iter()
+        .max_by(|p, q| p.radius.total_cmp(&q.radius))
+        .unwrap();
+    }
+    let center = a + vec2(
+        ac.y * ab.magnitude2() - ab.y * ac.magnitude2(),
+        ab.x * ac.magnitude2() - ac.x * ab.magnitude2(),
+    ) / d;
+    Circle::new(
+        center,
+        [a, b, c]
+            .iter()
+            .map(|p| center.distance(*p))
+            .fold(0.0, f64::max),
+    )
+}
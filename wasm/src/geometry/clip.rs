@@ -0,0 +1,322 @@
+// Clipping filled paths to rectangles and convex polygons, keeping curves as curves
+
+use std::cmp::Ordering;
+
+use cgmath::{vec2, InnerSpace, Point2, Vector2};
+
+use super::{
+    path::Path,
+    polynomial::{solve_cubic, solve_linear, solve_quadratic},
+    rect::Rect,
+    segment::Segment,
+};
+
+// The region where dot(normal, point) <= offset (`normal` has unit length)
+#[derive(Clone, Copy, Debug)]
+struct HalfPlane {
+    normal: Vector2<f64>,
+    offset: f64,
+}
+
+impl HalfPlane {
+    fn distance(&self, point: Point2<f64>) -> f64 {
+        self.normal.dot(vec2(point.x, point.y)) - self.offset
+    }
+
+    fn project(&self, point: Point2<f64>) -> Point2<f64> {
+        point - self.normal * self.distance(point)
+    }
+
+    // Parameters in (0, 1) where the segment crosses the boundary line
+    fn crossings(&self, segment: &Segment) -> Vec<f64> {
+        // signed distances of the control points are the Bernstein coefficients of the distance along the curve
+        let d: Vec<f64> = segment
+            .points()
+            .iter()
+            .map(|&point| self.distance(point))
+            .collect();
+        let mut roots = match d[..] {
+            [d0, d1] => solve_linear(d1 - d0, d0),
+            [d0, d1, d2] => solve_quadratic(d0 - 2.0 * d1 + d2, 2.0 * (d1 - d0), d0),
+            [d0, d1, d2, d3] => solve_cubic(
+                d3 - d0 + 3.0 * (d1 - d2),
+                3.0 * (d0 - 2.0 * d1 + d2),
+                3.0 * (d1 - d0),
+                d0,
+            ),
+            _ => Vec::new(),
+        };
+        roots.retain(|t| *t > 1e-9 && *t < 1.0 - 1e-9);
+        roots.sort_by(f64::total_cmp);
+        roots.dedup();
+        roots
+    }
+}
+
+impl Path {
+    // Intersection of the filled region with the rectangle (open subpaths are implicitly closed).
+    // Curves inside the rectangle are kept, curves crossing its edges are split at the crossings.
+    pub fn clip_to_rect(&self, rect: &Rect) -> Path {
+        match self.bounds() {
+            Some(bounds) if rect.contains_rect(&bounds) => return self.clone(),
+            Some(bounds) if !rect.intersects(&bounds) => return Path::new(),
+            None => return Path::new(),
+            _ => {}
+        }
+        self.clip_to_half_planes(&[
+            HalfPlane {
+                normal: vec2(-1.0, 0.0),
+                offset: -rect.min.x,
+            },
+            HalfPlane {
+                normal: vec2(1.0, 0.0),
+                offset: rect.max.x,
+            },
+            HalfPlane {
+                normal: vec2(0.0, -1.0),
+                offset: -rect.min.y,
+            },
+            HalfPlane {
+                normal: vec2(0.0, 1.0),
+                offset: rect.max.y,
+            },
+        ])
+    }
+
+    // Intersection of the filled region with a convex polygon of either orientation
+    pub fn clip_to_convex_polygon(&self, polygon: &[Point2<f64>]) -> Path {
+        let n = polygon.len();
+        let area: f64 = (0..n)
+            .map(|i| {
+                let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+                a.x * b.y - b.x * a.y
+            })
+            .sum();
+        if area.abs().partial_cmp(&0.0) != Some(Ordering::Greater) {
+            return Path::new();
+        }
+
+        let half_planes: Vec<HalfPlane> = (0..n)
+            .filter_map(|i| {
+                let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+                let edge = b - a;
+                let length = edge.magnitude();
+                if length.partial_cmp(&0.0) != Some(Ordering::Greater) {
+                    return None;
+                }
+                // outward normal: to the right of the edges of a counter-clockwise polygon
+                let normal = vec2(edge.y, -edge.x) * (area.signum() / length);
+                Some(HalfPlane {
+                    normal,
+                    offset: normal.dot(vec2(a.x, a.y)),
+                })
+            })
+            .collect();
+        self.clip_to_half_planes(&half_planes)
+    }
+
+    fn clip_to_half_planes(&self, half_planes: &[HalfPlane]) -> Path {
+        let mut path = Path::new();
+        for subpath in self.subpaths() {
+            let mut segments = subpath.segments.clone();
+            if subpath.end() != subpath.start() {
+                segments.push(Segment::Line(subpath.end(), subpath.start()));
+            }
+            for half_plane in half_planes {
+                if segments.is_empty() {
+                    break;
+                }
+                segments = clip_loop(&segments, half_plane);
+            }
+
+            if let Some(first) = segments.first() {
+                path.move_to(first.start());
+                for segment in &segments {
+                    path.push_segment(segment);
+                }
+                path.close();
+            }
+        }
+        path
+    }
+}
+
+// Sutherland-Hodgman on curves: keeps the parts of a closed loop inside the half-plane
+// and bridges the gaps with lines along the boundary.
+fn clip_loop(segments: &[Segment], half_plane: &HalfPlane) -> Vec<Segment> {
+    let scale = segments
+        .iter()
+        .flat_map(|segment| segment.points())
+        .fold(half_plane.offset.abs(), |scale, point| {
+            scale.max(point.x.abs()).max(point.y.abs())
+        });
+    let epsilon = 1e-9 * scale.max(1.0);
+
+    let mut clipped: Vec<Segment> = Vec::new();
+    for segment in segments {
+        let mut bounds = vec![0.0];
+        bounds.extend(half_plane.crossings(segment));
+        bounds.push(1.0);
+
+        for range in bounds.windows(2) {
+            let (t0, t1) = (range[0], range[1]);
+            if half_plane.distance(segment.point_at((t0 + t1) / 2.0)) > epsilon {
+                continue;
+            }
+            let piece = segment.subsegment(t0, t1);
+            // endpoints at crossings are snapped onto the boundary, the others are kept exact
+            let mut points = piece.points();
+            let last = points.len() - 1;
+            points[0] = if t0 > 0.0 {
+                half_plane.project(points[0])
+            } else {
+                segment.start()
+            };
+            points[last] = if t1 < 1.0 {
+                half_plane.project(points[last])
+            } else {
+                segment.end()
+            };
+            let piece = segment_from_points(&points);
+
+            if let Some(previous) = clipped.last() {
+                if previous.end() != piece.start() {
+                    clipped.push(Segment::Line(previous.end(), piece.start()));
+                }
+            }
+            clipped.push(piece);
+        }
+    }
+
+    if let (Some(first), Some(last)) = (clipped.first(), clipped.last()) {
+        if last.end() != first.start() {
+            clipped.push(Segment::Line(last.end(), first.start()));
+        }
+    }
+    clipped
+}
+
+fn segment_from_points(points: &[Point2<f64>]) -> Segment {
+    match *points {
+        [p0, p1] => Segment::Line(p0, p1),
+        [p0, p1, p2] => Segment::Quad(p0, p1, p2),
+        [p0, p1, p2, p3] => Segment::Cubic(p0, p1, p2, p3),
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::point2;
+
+    use super::*;
+    use crate::geometry::fill_rule::FillRule;
+
+    fn area(path: &Path) -> f64 {
+        path.to_polygons()
+            .iter()
+            .map(|polygon| {
+                let n = polygon.len();
+                (0..n)
+                    .map(|i| {
+                        let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+                        a.x * b.y - b.x * a.y
+                    })
+                    .sum::<f64>()
+                    / 2.0
+            })
+            .sum()
+    }
+
+    fn rect(min: f64, max: f64) -> Rect {
+        Rect::new(point2(min, min), point2(max, max))
+    }
+
+    fn square() -> Path {
+        Path::from_svg("M0 0 H10 V10 H0 Z").unwrap()
+    }
+
+    #[test]
+    fn partial_overlap_keeps_the_intersection() {
+        let clipped = square().clip_to_rect(&rect(5.0, 15.0));
+        assert_eq!(clipped.bounds(), Some(rect(5.0, 10.0)));
+        assert!((area(&clipped) - 25.0).abs() < 1e-9);
+
+        // curves inside the rect stay curves
+        let circle = Path::from_svg("M0 -5 A5 5 0 0 1 0 5 A5 5 0 0 1 0 -5 Z").unwrap();
+        let half = circle.clip_to_rect(&Rect::new(point2(0.0, -10.0), point2(10.0, 10.0)));
+        assert!(half
+            .to_segments()
+            .iter()
+            .any(|segment| matches!(segment, Segment::Cubic(..))));
+        let bounds = half.bounds().unwrap();
+        assert!(bounds.min.x.abs() < 1e-9 && (bounds.max.x - 5.0).abs() < 1e-9);
+        assert!((area(&circle) / 2.0 - area(&half)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn contained_inputs_are_kept_whole() {
+        // path inside the rect: unchanged
+        let path = square();
+        assert_eq!(
+            path.clip_to_rect(&rect(-1.0, 11.0)).commands(),
+            path.commands()
+        );
+
+        // rect inside the path: the rect itself
+        let clipped = square().clip_to_rect(&rect(2.0, 4.0));
+        assert_eq!(clipped.bounds(), Some(rect(2.0, 4.0)));
+        assert!((area(&clipped) - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn disjoint_inputs_give_an_empty_path() {
+        assert!(square()
+            .clip_to_rect(&rect(20.0, 30.0))
+            .commands()
+            .is_empty());
+        assert!(square()
+            .clip_to_convex_polygon(&[point2(20.0, 0.0), point2(30.0, 0.0), point2(25.0, 5.0)])
+            .commands()
+            .is_empty());
+    }
+
+    #[test]
+    fn clipping_keeps_holes() {
+        // the hole has the same orientation, so it only shows with even-odd
+        let path = Path::from_svg("M0 0 H10 V10 H0 Z M3 3 H7 V7 H3 Z").unwrap();
+        let clipped = path.clip_to_rect(&rect(5.0, 15.0));
+        for fill_rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            for point in [point2(6.0, 6.0), point2(8.0, 8.0), point2(6.0, 9.0)] {
+                assert_eq!(
+                    clipped.contains_point(point, fill_rule),
+                    path.contains_point(point, fill_rule)
+                );
+            }
+        }
+        assert!(!clipped.contains_point(point2(6.0, 6.0), FillRule::EvenOdd));
+    }
+
+    #[test]
+    fn convex_polygons_of_either_orientation() {
+        let triangle = [point2(0.0, 0.0), point2(20.0, 0.0), point2(0.0, 20.0)];
+        let mut reversed = triangle;
+        reversed.reverse();
+        for polygon in [triangle, reversed] {
+            let clipped = square().clip_to_convex_polygon(&polygon);
+            // the square is inside the triangle's hypotenuse x + y <= 20
+            assert!((area(&clipped) - 100.0).abs() < 1e-9);
+        }
+        let clipped = square().clip_to_convex_polygon(&[
+            point2(0.0, 0.0),
+            point2(10.0, 0.0),
+            point2(0.0, 10.0),
+        ]);
+        assert!((area(&clipped) - 50.0).abs() < 1e-9);
+        // degenerate polygons clip everything away
+        assert!(square()
+            .clip_to_convex_polygon(&[point2(0.0, 0.0), point2(5.0, 5.0)])
+            .commands()
+            .is_empty());
+    }
+}
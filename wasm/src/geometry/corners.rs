@@ -0,0 +1,254 @@
+// Corner rounding (fillet) and chamfering
+
+use std::{cmp::Ordering, f64::consts::PI};
+
+use cgmath::{InnerSpace, Point2, Vector2};
+
+use super::{path::Path, segment::Segment};
+
+// joins turning less than this (radians) are treated as smooth and left alone
+const SMOOTH_ANGLE: f64 = 1e-3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CornerStyle {
+    Round,
+    Chamfer,
+}
+
+impl Path {
+    // Replaces every sharp corner with a tangent arc of `radius`
+    pub fn round_corners(&self, radius: f64) -> Path {
+        self.modify_corners(CornerStyle::Round, |_| radius)
+    }
+
+    // Like `round_corners` with one radius per vertex.
+    // Vertices are numbered in order through all subpaths: the start point, then each segment's end point
+    // (a closed subpath's final point coincides with its start and is not numbered again).
+    // Missing radii are treated as zero.
+    pub fn round_corners_per_vertex(&self, radii: &[f64]) -> Path {
+        self.modify_corners(CornerStyle::Round, |vertex| {
+            radii.get(vertex).copied().unwrap_or(0.0)
+        })
+    }
+
+    // Cuts every sharp corner with a straight line, `distance` away from the corner along each side
+    pub fn chamfer_corners(&self, distance: f64) -> Path {
+        self.modify_corners(CornerStyle::Chamfer, |_| distance)
+    }
+
+    // Like `chamfer_corners` with one distance per vertex (numbered as in `round_corners_per_vertex`)
+    pub fn chamfer_corners_per_vertex(&self, distances: &[f64]) -> Path {
+        self.modify_corners(CornerStyle::Chamfer, |vertex| {
+            distances.get(vertex).copied().unwrap_or(0.0)
+        })
+    }
+
+    fn modify_corners(&self, style: CornerStyle, size: impl Fn(usize) -> f64) -> Path {
+        let mut path = Path::new();
+        let mut vertex_base = 0;
+
+        for subpath in self.subpaths() {
+            let mut segments = subpath.segments.clone();
+            let has_closing_line = subpath.closed && subpath.end() != subpath.start();
+            if has_closing_line {
+                segments.push(Segment::Line(subpath.end(), subpath.start()));
+            }
+            let n = segments.len();
+            let join_count = if subpath.closed { n } else { n - 1 };
+
+            // distance trimmed from each side of each join (join k is between segments k and k + 1)
+            let mut trims = vec![0.0; join_count];
+            for (k, trim) in trims.iter_mut().enumerate() {
+                let incoming = &segments[k];
+                let outgoing = &segments[(k + 1) % n];
+                let angle = incoming
+                    .end_tangent()
+                    .angle(outgoing.start_tangent())
+                    .0
+                    .abs();
+                if angle.partial_cmp(&SMOOTH_ANGLE) != Some(Ordering::Greater) {
+                    continue;
+                }
+                let vertex = vertex_base + (k + 1) % if subpath.closed { n } else { n + 1 };
+                let size = size(vertex).max(0.0);
+                let distance = match style {
+                    CornerStyle::Round => size * (angle.min(PI - SMOOTH_ANGLE) / 2.0).tan(),
+                    CornerStyle::Chamfer => size,
+                };
+                // adjacent corners share each segment, so each may use at most half of it
+                let limit = incoming.length().min(outgoing.length()) / 2.0;
+                *trim = distance.min(limit);
+            }
+
+            // parameter range kept of each segment
+            let ranges: Vec<(f64, f64)> = (0..n)
+                .map(|k| {
+                    let segment = &segments[k];
+                    let start_trim = if subpath.closed || k > 0 {
+                        trims[(k + n - 1) % n]
+                    } else {
+                        0.0
+                    };
+                    let end_trim = if k < join_count { trims[k] } else { 0.0 };
+                    (
+                        segment.parameter_at_length(start_trim),
+                        segment.parameter_at_length(segment.length() - end_trim),
+                    )
+                })
+                .collect();
+
+            path.move_to(segments[0].point_at(ranges[0].0));
+            for k in 0..n {
+                let (t0, t1) = ranges[k];
+                // an untrimmed end of the closing line is drawn by `close`
+                let is_implicit = has_closing_line && k == n - 1 && t1 >= 1.0;
+                if t0 < t1 && !is_implicit {
+                    path.push_segment(&segments[k].subsegment(t0, t1));
+                }
+                if k < join_count && trims[k] > 0.0 {
+                    let next = (k + 1) % n;
+                    append_corner(
+                        &mut path,
+                        style,
+                        tangent_at(&segments[k], t1),
+                        segments[next].point_at(ranges[next].0),
+                        tangent_at(&segments[next], ranges[next].0),
+                    );
+                }
+            }
+            if subpath.closed {
+                path.close();
+            }
+
+            vertex_base += if subpath.closed { n } else { n + 1 };
+        }
+
+        path
+    }
+}
+
+// Curve direction at t, falling back to the end tangents where the derivative vanishes
+fn tangent_at(segment: &Segment, t: f64) -> Vector2<f64> {
+    let derivative = segment.derivative_at(t);
+    let tangent = if derivative.magnitude2() > 0.0 {
+        derivative
+    } else if t < 0.5 {
+        segment.start_tangent()
+    } else {
+        segment.end_tangent()
+    };
+    tangent.normalize()
+}
+
+// Connects the current point to `to`, leaving the current point along `tangent_in`
+// and arriving along `tangent_out`
+fn append_corner(
+    path: &mut Path,
+    style: CornerStyle,
+    tangent_in: Vector2<f64>,
+    to: Point2<f64>,
+    tangent_out: Vector2<f64>,
+) {
+    let from = path.current_point();
+    match style {
+        CornerStyle::Chamfer => path.line_to(to),
+        CornerStyle::Round => {
+            let angle = tangent_in.angle(tangent_out).0.abs();
+            // cubic approximation of the circular arc through both points with matching tangents
+            let chord = (to - from).magnitude();
+            let radius = chord / (2.0 * (angle / 2.0).sin());
+            let handle = 4.0 / 3.0 * (angle / 4.0).tan() * radius;
+            path.cubic_to(from + tangent_in * handle, to - tangent_out * handle, to);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::point2;
+
+    use super::*;
+    use crate::geometry::path::PathCommand;
+
+    fn end_points(path: &Path) -> Vec<Point2<f64>> {
+        path.commands()
+            .iter()
+            .filter_map(|command| match *command {
+                PathCommand::MoveTo(point)
+                | PathCommand::LineTo(point)
+                | PathCommand::QuadTo(_, point)
+                | PathCommand::CubicTo(_, _, point) => Some(point),
+                PathCommand::Close => None,
+            })
+            .collect()
+    }
+
+    fn has_end_point(path: &Path, x: f64, y: f64) -> bool {
+        end_points(path)
+            .iter()
+            .any(|point| (point - point2(x, y)).magnitude() < 1e-9)
+    }
+
+    #[test]
+    fn clamps_the_radius_to_half_of_the_shorter_edge() {
+        let rect = Path::from_svg("M0 0 H10 V1 H0 Z").unwrap();
+        for path in [rect.round_corners(5.0), rect.chamfer_corners(5.0)] {
+            // each corner may only use half of the 1 unit edges
+            for (x, y) in [
+                (0.5, 0.0),
+                (9.5, 0.0),
+                (10.0, 0.5),
+                (9.5, 1.0),
+                (0.5, 1.0),
+                (0.0, 0.5),
+            ] {
+                assert!(has_end_point(&path, x, y), "{:?} missing", (x, y));
+            }
+            for (x, y) in [(0.0, 0.0), (10.0, 0.0), (10.0, 1.0), (0.0, 1.0)] {
+                assert!(!has_end_point(&path, x, y), "{:?} not removed", (x, y));
+            }
+            // the short edges are used up entirely, leaving no empty segments
+            for segment in path.to_segments() {
+                assert!(segment.length() > 1e-9);
+            }
+            let bounds = path.bounds().unwrap();
+            assert!((bounds.min - point2(0.0, 0.0)).magnitude() < 1e-9);
+            assert!((bounds.max - point2(10.0, 1.0)).magnitude() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn rounds_each_vertex_with_its_own_radius() {
+        let square = Path::from_svg("M0 0 H10 V10 H0 Z").unwrap();
+        let path = square.round_corners_per_vertex(&[0.0, 2.0, 0.0, 3.0]);
+        assert!(has_end_point(&path, 0.0, 0.0));
+        assert!(has_end_point(&path, 10.0, 10.0));
+        for (x, y) in [(8.0, 0.0), (10.0, 2.0), (3.0, 10.0), (0.0, 7.0)] {
+            assert!(has_end_point(&path, x, y), "{:?} missing", (x, y));
+        }
+        assert!(!has_end_point(&path, 10.0, 0.0));
+        assert!(!has_end_point(&path, 0.0, 10.0));
+
+        // missing radii are zero; the subpath now starts after the trimmed first corner
+        let path = square.chamfer_corners_per_vertex(&[1.0]);
+        assert_eq!(
+            end_points(&path),
+            vec![
+                point2(1.0, 0.0),
+                point2(10.0, 0.0),
+                point2(10.0, 10.0),
+                point2(0.0, 10.0),
+                point2(0.0, 1.0),
+                point2(1.0, 0.0)
+            ]
+        );
+
+        // an open subpath's end points are vertices too, but not corners
+        let open = Path::from_svg("M0 0 H10 V10").unwrap();
+        let path = open.round_corners_per_vertex(&[5.0, 1.0, 5.0]);
+        assert_eq!(end_points(&path).len(), 4);
+        assert!(has_end_point(&path, 9.0, 0.0));
+        assert!(has_end_point(&path, 10.0, 1.0));
+        assert!(has_end_point(&path, 10.0, 10.0));
+    }
+}
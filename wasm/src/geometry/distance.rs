@@ -0,0 +1,100 @@
+// Exact distances from points to segments and paths
+
+use cgmath::{InnerSpace, MetricSpace, Point2, Vector2};
+
+use super::{path::Path, polynomial::solve_cubic, segment::Segment};
+
+// Starting points of the Newton iterations on cubics
+const CUBIC_SEARCH_STARTS: usize = 8;
+const CUBIC_NEWTON_ITERATIONS: usize = 8;
+
+impl Segment {
+    pub fn second_derivative_at(&self, t: f64) -> Vector2<f64> {
+        match *self {
+            Segment::Line(..) => Vector2::new(0.0, 0.0),
+            Segment::Quad(p0, p1, p2) => (p2 - p1) * 2.0 - (p1 - p0) * 2.0,
+            Segment::Cubic(p0, p1, p2, p3) => {
+                ((p2 - p1) - (p1 - p0)) * (6.0 * (1.0 - t)) + ((p3 - p2) - (p2 - p1)) * (6.0 * t)
+            }
+        }
+    }
+
+    // Parameter of the point on the segment closest to `point`
+    pub fn closest_parameter(&self, point: Point2<f64>) -> f64 {
+        let mut candidates = vec![0.0, 1.0];
+        match *self {
+            Segment::Line(p0, p1) => {
+                let direction = p1 - p0;
+                let length2 = direction.magnitude2();
+                if length2 > 0.0 {
+                    candidates.push(((point - p0).dot(direction) / length2).clamp(0.0, 1.0));
+                }
+            }
+            Segment::Quad(p0, p1, p2) => {
+                // (B(t) - point) . B'(t) = 0 is a cubic in t
+                let (a, b, m) = (p1 - p0, (p2 - p1) - (p1 - p0), p0 - point);
+                candidates.extend(
+                    solve_cubic(
+                        b.dot(b),
+                        3.0 * a.dot(b),
+                        2.0 * a.dot(a) + m.dot(b),
+                        m.dot(a),
+                    )
+                    .into_iter()
+                    .filter(|t| (0.0..=1.0).contains(t)),
+                );
+            }
+            Segment::Cubic(..) => {
+                // the quintic is solved numerically from several starting points
+                for start in 0..=CUBIC_SEARCH_STARTS {
+                    let mut t = start as f64 / CUBIC_SEARCH_STARTS as f64;
+                    for _ in 0..CUBIC_NEWTON_ITERATIONS {
+                        let offset = self.point_at(t) - point;
+                        let derivative = self.derivative_at(t);
+                        let numerator = offset.dot(derivative);
+                        let denominator =
+                            derivative.magnitude2() + offset.dot(self.second_derivative_at(t));
+                        if denominator == 0.0 {
+                            break;
+                        }
+                        t = (t - numerator / denominator).clamp(0.0, 1.0);
+                    }
+                    candidates.push(t);
+                }
+            }
+        }
+        candidates
+            .into_iter()
+            .min_by(|&a, &b| {
+                self.point_at(a)
+                    .distance2(point)
+                    .total_cmp(&self.point_at(b).distance2(point))
+            })
+            .unwrap()
+    }
+
+    pub fn closest_point(&self, point: Point2<f64>) -> Point2<f64> {
+        self.point_at(self.closest_parameter(point))
+    }
+
+    pub fn distance_to(&self, point: Point2<f64>) -> f64 {
+        self.closest_point(point).distance(point)
+    }
+}
+
+impl Path {
+    // Distance from the point to the outline, including the implicit closing lines of open subpaths
+    pub fn distance_to(&self, point: Point2<f64>) -> f64 {
+        self.subpaths()
+            .iter()
+            .flat_map(|subpath| {
+                let mut segments = subpath.segments.clone();
+                if subpath.end() != subpath.start() {
+                    segments.push(Segment::Line(subpath.end(), subpath.start()));
+                }
+                segments
+            })
+            .map(|segment| segment.distance_to(point))
+            .fold(f64::INFINITY, f64::min)
+    }
+}
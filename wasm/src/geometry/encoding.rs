@@ -0,0 +1,287 @@
+// Compact binary form of a Path: one verb byte per command and a flat x,y coordinate array.
+// Both arrays map directly to JS typed arrays (Uint8Array + Float32Array/Float64Array),
+// and decoding only borrows them.
+
+use std::fmt;
+
+use cgmath::{point2, Point2};
+
+use super::{
+    error::PathError,
+    path::{Path, PathCommand},
+};
+
+pub const VERB_MOVE_TO: u8 = 0;
+pub const VERB_LINE_TO: u8 = 1;
+pub const VERB_QUAD_TO: u8 = 2;
+pub const VERB_CUBIC_TO: u8 = 3;
+pub const VERB_CLOSE: u8 = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DecodeError {
+    UnknownVerb { command_index: usize, verb: u8 },
+    CoordinateCountMismatch { expected: usize, actual: usize },
+    // well-formed buffers that describe an invalid path
+    Path(PathError),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnknownVerb {
+                command_index,
+                verb,
+            } => {
+                write!(f, "command {} has an unknown verb {}", command_index, verb)
+            }
+            DecodeError::CoordinateCountMismatch { expected, actual } => {
+                write!(f, "expected {} coordinates but got {}", expected, actual)
+            }
+            DecodeError::Path(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<PathError> for DecodeError {
+    fn from(error: PathError) -> DecodeError {
+        DecodeError::Path(error)
+    }
+}
+
+pub trait Coordinate: Copy {
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+}
+
+impl Coordinate for f32 {
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Coordinate for f64 {
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EncodedPath<T: Coordinate> {
+    pub verbs: Vec<u8>,
+    pub coordinates: Vec<T>,
+}
+
+// number of points following each verb
+fn point_count(verb: u8) -> Option<usize> {
+    match verb {
+        VERB_MOVE_TO | VERB_LINE_TO => Some(1),
+        VERB_QUAD_TO => Some(2),
+        VERB_CUBIC_TO => Some(3),
+        VERB_CLOSE => Some(0),
+        _ => None,
+    }
+}
+
+impl Path {
+    pub fn encode<T: Coordinate>(&self) -> EncodedPath<T> {
+        let mut verbs = Vec::with_capacity(self.commands().len());
+        let mut coordinates = Vec::new();
+        let mut push = |point: &Point2<f64>| {
+            coordinates.push(T::from_f64(point.x));
+            coordinates.push(T::from_f64(point.y));
+        };
+
+        for command in self.commands() {
+            match command {
+                PathCommand::MoveTo(point) => {
+                    verbs.push(VERB_MOVE_TO);
+                    push(point);
+                }
+                PathCommand::LineTo(point) => {
+                    verbs.push(VERB_LINE_TO);
+                    push(point);
+                }
+                PathCommand::QuadTo(control, point) => {
+                    verbs.push(VERB_QUAD_TO);
+                    push(control);
+                    push(point);
+                }
+                PathCommand::CubicTo(control1, control2, point) => {
+                    verbs.push(VERB_CUBIC_TO);
+                    push(control1);
+                    push(control2);
+                    push(point);
+                }
+                PathCommand::Close => {
+                    verbs.push(VERB_CLOSE);
+                }
+            }
+        }
+
+        EncodedPath { verbs, coordinates }
+    }
+
+    pub fn decode<T: Coordinate>(verbs: &[u8], coordinates: &[T]) -> Result<Path, DecodeError> {
+        let mut expected = 0;
+        for (index, verb) in verbs.iter().enumerate() {
+            match point_count(*verb) {
+                Some(count) => expected += count * 2,
+                None => {
+                    return Err(DecodeError::UnknownVerb {
+                        command_index: index,
+                        verb: *verb,
+                    })
+                }
+            }
+        }
+        if expected != coordinates.len() {
+            return Err(DecodeError::CoordinateCountMismatch {
+                expected,
+                actual: coordinates.len(),
+            });
+        }
+
+        let mut points = coordinates
+            .chunks_exact(2)
+            .map(|xy| point2(xy[0].to_f64(), xy[1].to_f64()));
+        let mut next = || points.next().unwrap();
+
+        let commands = verbs
+            .iter()
+            .map(|verb| match *verb {
+                VERB_MOVE_TO => PathCommand::MoveTo(next()),
+                VERB_LINE_TO => PathCommand::LineTo(next()),
+                VERB_QUAD_TO => PathCommand::QuadTo(next(), next()),
+                VERB_CUBIC_TO => PathCommand::CubicTo(next(), next(), next()),
+                _ => PathCommand::Close,
+            })
+            .collect();
+
+        Ok(Path::from_commands(commands)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Path {
+        Path::from_svg("M0.5 1 L2 3 Q4 5 6 7 C8 9 10 11 12.25 -13 Z M1 1 L2 2").unwrap()
+    }
+
+    #[test]
+    fn encodes_one_verb_per_command() {
+        let encoded = example().encode::<f64>();
+        assert_eq!(
+            encoded.verbs,
+            vec![
+                VERB_MOVE_TO,
+                VERB_LINE_TO,
+                VERB_QUAD_TO,
+                VERB_CUBIC_TO,
+                VERB_CLOSE,
+                VERB_MOVE_TO,
+                VERB_LINE_TO
+            ]
+        );
+        assert_eq!(encoded.coordinates.len(), 2 * (1 + 1 + 2 + 3 + 1 + 1));
+        assert_eq!(&encoded.coordinates[..4], &[0.5, 1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn round_trips_through_f64_and_f32() {
+        let path = example();
+        let encoded = path.encode::<f64>();
+        let decoded = Path::decode(&encoded.verbs, &encoded.coordinates).unwrap();
+        assert_eq!(decoded.commands(), path.commands());
+
+        // the example's coordinates are exact in f32
+        let encoded = path.encode::<f32>();
+        let decoded = Path::decode(&encoded.verbs, &encoded.coordinates).unwrap();
+        assert_eq!(decoded.commands(), path.commands());
+    }
+
+    #[test]
+    fn decoding_empty_buffers_gives_an_empty_path() {
+        let path = Path::decode::<f32>(&[], &[]).unwrap();
+        assert!(path.commands().is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_verbs() {
+        let error = Path::decode::<f32>(&[VERB_MOVE_TO, VERB_CLOSE, 5], &[0.0, 0.0]).unwrap_err();
+        assert_eq!(
+            error,
+            DecodeError::UnknownVerb {
+                command_index: 2,
+                verb: 5
+            }
+        );
+        assert_eq!(error.to_string(), "command 2 has an unknown verb 5");
+    }
+
+    #[test]
+    fn rejects_coordinate_count_mismatches() {
+        let encoded = example().encode::<f32>();
+        for coordinates in [
+            &encoded.coordinates[..encoded.coordinates.len() - 2],
+            &[encoded.coordinates.as_slice(), &[1.0]].concat(),
+        ] {
+            assert_eq!(
+                Path::decode(&encoded.verbs, coordinates).unwrap_err(),
+                DecodeError::CoordinateCountMismatch {
+                    expected: encoded.coordinates.len(),
+                    actual: coordinates.len()
+                }
+            );
+        }
+        assert_eq!(
+            Path::decode::<f64>(&[VERB_MOVE_TO, VERB_LINE_TO], &[0.0, 0.0, 1.0])
+                .unwrap_err()
+                .to_string(),
+            "expected 4 coordinates but got 3"
+        );
+    }
+
+    #[test]
+    fn decoded_commands_are_validated() {
+        assert_eq!(
+            Path::decode::<f32>(&[VERB_LINE_TO], &[1.0, 1.0]).unwrap_err(),
+            DecodeError::Path(PathError::MissingMoveTo { command_index: 0 })
+        );
+        assert_eq!(
+            Path::decode::<f32>(&[VERB_MOVE_TO], &[f32::NAN, 1.0]).unwrap_err(),
+            DecodeError::Path(PathError::NonFiniteCoordinate { command_index: 0 })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn round_trips_through_serde() {
+        let path = example();
+        let json = serde_json::to_string(&path).unwrap();
+        let decoded: Path = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.commands(), path.commands());
+
+        let encoded = path.encode::<f32>();
+        let json = serde_json::to_string(&encoded).unwrap();
+        assert_eq!(
+            serde_json::from_str::<EncodedPath<f32>>(&json).unwrap(),
+            encoded
+        );
+
+        // deserializing validates like `Path::from_commands`
+        assert!(serde_json::from_str::<Path>(r#"[{"LineTo":{"x":1.0,"y":2.0}}]"#).is_err());
+    }
+}
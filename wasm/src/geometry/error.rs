@@ -0,0 +1,57 @@
+use std::fmt;
+
+// Path commands that can't be drawn; other modules wrap this in their own error types
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathError {
+    NonFiniteCoordinate { command_index: usize },
+    MissingMoveTo { command_index: usize },
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathError::NonFiniteCoordinate { command_index } => {
+                write!(f, "command {} has a non-finite coordinate", command_index)
+            }
+            PathError::MissingMoveTo { command_index } => {
+                write!(f, "command {} is not preceded by a MoveTo", command_index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::point2;
+
+    use super::*;
+    use crate::geometry::path::{Path, PathCommand};
+
+    fn assert_error<T: fmt::Debug>(result: Result<T, PathError>, error: PathError, message: &str) {
+        assert_eq!(result.unwrap_err(), error);
+        assert_eq!(error.to_string(), message);
+    }
+
+    #[test]
+    fn non_finite_coordinate() {
+        assert_error(
+            Path::from_commands(vec![
+                PathCommand::MoveTo(point2(0.0, 0.0)),
+                PathCommand::LineTo(point2(1.0, f64::NAN)),
+            ]),
+            PathError::NonFiniteCoordinate { command_index: 1 },
+            "command 1 has a non-finite coordinate",
+        );
+    }
+
+    #[test]
+    fn missing_move_to() {
+        assert_error(
+            Path::from_commands(vec![PathCommand::LineTo(point2(1.0, 1.0))]),
+            PathError::MissingMoveTo { command_index: 0 },
+            "command 0 is not preceded by a MoveTo",
+        );
+    }
+}
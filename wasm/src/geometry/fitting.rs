@@ -0,0 +1,201 @@
+// Least-squares fitting of cubic Bézier curves to digitized points
+// port of Philip J. Schneider, "An Algorithm for Automatically Fitting Digitized Curves" (Graphics Gems, 1990)
+
+use cgmath::{EuclideanSpace, InnerSpace, Point2, Vector2};
+
+use super::segment::Segment;
+
+const MAX_REPARAMETERIZATIONS: usize = 4;
+
+// `start_tangent` points from the first point into the curve, `end_tangent` from the last point back into the curve
+pub fn fit_cubics_with_tangents(
+    points: &[Point2<f64>],
+    start_tangent: Vector2<f64>,
+    end_tangent: Vector2<f64>,
+    tolerance: f64,
+) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    if points.len() >= 2 {
+        fit_recursive(
+            points,
+            start_tangent,
+            end_tangent,
+            tolerance * tolerance,
+            &mut segments,
+        );
+    }
+    segments
+}
+
+fn fit_recursive(
+    points: &[Point2<f64>],
+    start_tangent: Vector2<f64>,
+    end_tangent: Vector2<f64>,
+    error_square: f64,
+    segments: &mut Vec<Segment>,
+) {
+    let n = points.len();
+    if n == 2 {
+        let distance = (points[1] - points[0]).magnitude() / 3.0;
+        segments.push(Segment::Cubic(
+            points[0],
+            points[0] + start_tangent * distance,
+            points[1] + end_tangent * distance,
+            points[1],
+        ));
+        return;
+    }
+
+    let mut u = chord_length_parameterize(points);
+    let mut bezier = generate_bezier(points, &u, start_tangent, end_tangent);
+    let (mut max_error, mut split) = compute_max_error(points, &bezier, &u);
+    if max_error < error_square {
+        segments.push(bezier);
+        return;
+    }
+
+    // if the error is not too large, try improving the parameterization
+    if max_error < error_square * 4.0 {
+        for _ in 0..MAX_REPARAMETERIZATIONS {
+            u = reparameterize(points, &u, &bezier);
+            bezier = generate_bezier(points, &u, start_tangent, end_tangent);
+            (max_error, split) = compute_max_error(points, &bezier, &u);
+            if max_error < error_square {
+                segments.push(bezier);
+                return;
+            }
+        }
+    }
+
+    // fitting failed, split at the point of max error and fit recursively
+    let center_tangent = (points[split - 1] - points[split + 1]).normalize();
+    fit_recursive(
+        &points[..=split],
+        start_tangent,
+        center_tangent,
+        error_square,
+        segments,
+    );
+    fit_recursive(
+        &points[split..],
+        -center_tangent,
+        end_tangent,
+        error_square,
+        segments,
+    );
+}
+
+fn chord_length_parameterize(points: &[Point2<f64>]) -> Vec<f64> {
+    let mut u = vec![0.0; points.len()];
+    for i in 1..points.len() {
+        u[i] = u[i - 1] + (points[i] - points[i - 1]).magnitude();
+    }
+    let length = u[points.len() - 1];
+    if length > 0.0 {
+        for value in &mut u {
+            *value /= length;
+        }
+    }
+    u
+}
+
+fn bernstein(t: f64) -> [f64; 4] {
+    let mt = 1.0 - t;
+    [mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t]
+}
+
+// Least-squares cubic with the given end tangents
+fn generate_bezier(
+    points: &[Point2<f64>],
+    u: &[f64],
+    start_tangent: Vector2<f64>,
+    end_tangent: Vector2<f64>,
+) -> Segment {
+    let first = points[0];
+    let last = points[points.len() - 1];
+
+    let mut c = [[0.0; 2]; 2];
+    let mut x = [0.0; 2];
+    for (point, &t) in points.iter().zip(u) {
+        let b = bernstein(t);
+        let a0 = start_tangent * b[1];
+        let a1 = end_tangent * b[2];
+        c[0][0] += a0.dot(a0);
+        c[0][1] += a0.dot(a1);
+        c[1][1] += a1.dot(a1);
+        let tmp = point.to_vec() - (first.to_vec() * (b[0] + b[1]) + last.to_vec() * (b[2] + b[3]));
+        x[0] += a0.dot(tmp);
+        x[1] += a1.dot(tmp);
+    }
+    c[1][0] = c[0][1];
+
+    let det_c0_c1 = c[0][0] * c[1][1] - c[1][0] * c[0][1];
+    let det_c0_x = c[0][0] * x[1] - c[1][0] * x[0];
+    let det_x_c1 = x[0] * c[1][1] - x[1] * c[0][1];
+    let (alpha_start, alpha_end) = if det_c0_c1 == 0.0 {
+        (0.0, 0.0)
+    } else {
+        (det_x_c1 / det_c0_c1, det_c0_x / det_c0_c1)
+    };
+
+    // fall back to the Wu/Barsky heuristic when the solution is degenerate
+    let segment_length = (last - first).magnitude();
+    let epsilon = 1e-6 * segment_length;
+    if alpha_start < epsilon || alpha_end < epsilon {
+        let distance = segment_length / 3.0;
+        return Segment::Cubic(
+            first,
+            first + start_tangent * distance,
+            last + end_tangent * distance,
+            last,
+        );
+    }
+
+    Segment::Cubic(
+        first,
+        first + start_tangent * alpha_start,
+        last + end_tangent * alpha_end,
+        last,
+    )
+}
+
+// Returns the max squared distance and the index of the point where it occurs
+fn compute_max_error(points: &[Point2<f64>], bezier: &Segment, u: &[f64]) -> (f64, usize) {
+    let n = points.len();
+    let mut max_distance = 0.0;
+    let mut split = n / 2;
+    for i in 1..n - 1 {
+        let distance = (bezier.point_at(u[i]) - points[i]).magnitude2();
+        if distance >= max_distance {
+            max_distance = distance;
+            split = i;
+        }
+    }
+    (max_distance, split)
+}
+
+// One Newton-Raphson iteration towards the closest curve parameter of each point
+fn reparameterize(points: &[Point2<f64>], u: &[f64], bezier: &Segment) -> Vec<f64> {
+    let second_derivative = |t: f64| match *bezier {
+        Segment::Cubic(p0, p1, p2, p3) => {
+            ((p2 - p1) - (p1 - p0)) * (6.0 * (1.0 - t)) + ((p3 - p2) - (p2 - p1)) * (6.0 * t)
+        }
+        _ => Vector2::new(0.0, 0.0),
+    };
+
+    points
+        .iter()
+        .zip(u)
+        .map(|(point, &t)| {
+            let difference = bezier.point_at(t) - point;
+            let d1 = bezier.derivative_at(t);
+            let d2 = second_derivative(t);
+            let denominator = d1.dot(d1) + difference.dot(d2);
+            if denominator == 0.0 {
+                t
+            } else {
+                t - difference.dot(d1) / denominator
+            }
+        })
+        .collect()
+}
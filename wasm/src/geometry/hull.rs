@@ -0,0 +1,267 @@
+// Convex hulls and minimal bounding shapes of paths
+
+use cgmath::{vec2, InnerSpace, Point2, Vector2};
+
+use super::{circle::Circle, oriented_rect::OrientedRect, path::Path, segment::Segment};
+
+// Counter-clockwise (in y-up coordinates) convex polygon without collinear vertices
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConvexPolygon {
+    pub points: Vec<Point2<f64>>,
+}
+
+impl ConvexPolygon {
+    // Convex hull of the points (Andrew's monotone chain)
+    pub fn hull(points: &[Point2<f64>]) -> ConvexPolygon {
+        let mut sorted = points.to_vec();
+        sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        sorted.dedup();
+        if sorted.len() < 3 {
+            return ConvexPolygon { points: sorted };
+        }
+
+        // lower chain left to right and upper chain right to left, each ending where the other starts
+        let mut lower = Vec::new();
+        for &point in &sorted {
+            push_hull_point(&mut lower, point);
+        }
+        let mut upper = Vec::new();
+        for &point in sorted.iter().rev() {
+            push_hull_point(&mut upper, point);
+        }
+        lower.pop();
+        upper.pop();
+        lower.extend(upper);
+        ConvexPolygon { points: lower }
+    }
+
+    pub fn area(&self) -> f64 {
+        let n = self.points.len();
+        (0..n)
+            .map(|i| {
+                let (a, b) = (self.points[i], self.points[(i + 1) % n]);
+                a.x * b.y - b.x * a.y
+            })
+            .sum::<f64>()
+            / 2.0
+    }
+
+    pub fn contains(&self, point: Point2<f64>) -> bool {
+        let n = self.points.len();
+        n >= 3
+            && (0..n).all(|i| {
+                cross(
+                    self.points[(i + 1) % n] - self.points[i],
+                    point - self.points[i],
+                ) >= 0.0
+            })
+    }
+
+    // Smallest-area rectangle containing the polygon; one of its sides lies along a hull edge
+    pub fn minimum_area_rect(&self) -> Option<OrientedRect> {
+        let n = self.points.len();
+        let first = *self.points.first()?;
+        let axes: Vec<Vector2<f64>> = if n == 1 {
+            vec![vec2(1.0, 0.0)]
+        } else {
+            (0..n)
+                .map(|i| (self.points[(i + 1) % n] - self.points[i]).normalize())
+                .collect()
+        };
+
+        axes.into_iter()
+            .map(|axis| {
+                let normal = vec2(-axis.y, axis.x);
+                let (mut min, mut max) = (
+                    vec2(f64::INFINITY, f64::INFINITY),
+                    vec2(f64::NEG_INFINITY, f64::NEG_INFINITY),
+                );
+                for point in &self.points {
+                    let offset = point - first;
+                    let projected = vec2(offset.dot(axis), offset.dot(normal));
+                    min = vec2(min.x.min(projected.x), min.y.min(projected.y));
+                    max = vec2(max.x.max(projected.x), max.y.max(projected.y));
+                }
+                let middle = (min + max) * 0.5;
+                OrientedRect::new(
+                    first + axis * middle.x + normal * middle.y,
+                    axis,
+                    (max - min) * 0.5,
+                )
+            })
+            .min_by(|a, b| a.area().total_cmp(&b.area()))
+    }
+
+    pub fn to_path(&self) -> Path {
+        Path::from_polygons(std::slice::from_ref(&self.points))
+    }
+}
+
+// Adds a point to a monotone chain, dropping previous points that no longer make a left turn
+fn push_hull_point(chain: &mut Vec<Point2<f64>>, point: Point2<f64>) {
+    while chain.len() >= 2
+        && cross(
+            chain[chain.len() - 1] - chain[chain.len() - 2],
+            point - chain[chain.len() - 1],
+        ) <= 0.0
+    {
+        chain.pop();
+    }
+    chain.push(point);
+}
+
+fn cross(a: Vector2<f64>, b: Vector2<f64>) -> f64 {
+    a.x * b.y - a.y * b.x
+}
+
+impl Path {
+    // Convex hull of the path's outline.
+    // Curves are split into pieces and each piece lies inside the hull of its control points,
+    // so the hull contains the whole outline while the short pieces keep it close to the curves.
+    pub fn convex_hull(&self) -> ConvexPolygon {
+        let mut points = Vec::new();
+        for segment in self.to_segments() {
+            if let Segment::Line(start, end) = segment {
+                points.push(start);
+                points.push(end);
+                continue;
+            }
+            // four pieces per flattened line: how far control points stick out past the curve
+            // shrinks with the square of the piece length
+            let pieces = 4 * segment.flatten().len();
+            for i in 0..pieces {
+                let piece =
+                    segment.subsegment(i as f64 / pieces as f64, (i + 1) as f64 / pieces as f64);
+                points.extend(piece.points());
+            }
+        }
+        ConvexPolygon::hull(&points)
+    }
+
+    pub fn minimum_area_rect(&self) -> Option<OrientedRect> {
+        self.convex_hull().minimum_area_rect()
+    }
+
+    pub fn minimum_enclosing_circle(&self) -> Option<Circle> {
+        Circle::enclosing(&self.convex_hull().points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use cgmath::{point2, Basis2, MetricSpace, Rad, Rotation, Rotation2};
+
+    use super::*;
+
+    // true if the point is inside the polygon or within `epsilon` of it
+    fn encloses(polygon: &ConvexPolygon, point: Point2<f64>, epsilon: f64) -> bool {
+        let n = polygon.points.len();
+        (0..n).all(|i| {
+            let edge = polygon.points[(i + 1) % n] - polygon.points[i];
+            cross(edge, point - polygon.points[i]) >= -epsilon * edge.magnitude()
+        })
+    }
+
+    #[test]
+    fn hull_of_points_is_convex_and_counter_clockwise() {
+        let points = [
+            point2(0.0, 0.0),
+            point2(4.0, 0.0),
+            point2(2.0, 1.0),
+            point2(4.0, 4.0),
+            point2(2.0, 4.0),
+            point2(0.0, 4.0),
+            point2(1.0, 2.0),
+            point2(4.0, 0.0),
+        ];
+        let hull = ConvexPolygon::hull(&points);
+        // interior, duplicate and collinear points are dropped
+        assert_eq!(
+            hull.points,
+            vec![
+                point2(0.0, 0.0),
+                point2(4.0, 0.0),
+                point2(4.0, 4.0),
+                point2(0.0, 4.0)
+            ]
+        );
+        assert_eq!(hull.area(), 16.0);
+        assert!(hull.contains(point2(2.0, 1.0)) && !hull.contains(point2(5.0, 1.0)));
+
+        // fewer than three distinct points have no area
+        let hull = ConvexPolygon::hull(&[point2(1.0, 1.0), point2(1.0, 1.0)]);
+        assert_eq!(hull.points.len(), 1);
+        assert!(!hull.contains(point2(1.0, 1.0)));
+    }
+
+    #[test]
+    fn hull_contains_curves() {
+        let circle = Circle::new(point2(3.0, -2.0), 10.0);
+        let path = circle.to_path();
+        let hull = path.convex_hull();
+        for segment in path.to_segments() {
+            for i in 0..=200 {
+                let point = segment.point_at(i as f64 / 200.0);
+                assert!(encloses(&hull, point, 1e-9), "{:?} outside the hull", point);
+            }
+        }
+        // and stays close to them
+        assert!(hull.area() > circle.area() * 0.99 && hull.area() < circle.area() * 1.01);
+
+        // a bulging curve whose control points stick out much further than the curve
+        let path = Path::from_svg("M0 0 C0 10 10 10 10 0 Z").unwrap();
+        let hull = path.convex_hull();
+        let bounds = path.bounds().unwrap();
+        assert!(hull
+            .points
+            .iter()
+            .all(|point| point.y <= bounds.max.y + 0.1));
+        assert!(hull
+            .points
+            .iter()
+            .any(|point| (point.y - bounds.max.y).abs() < 0.1));
+    }
+
+    #[test]
+    fn minimum_area_rect_of_a_rotated_rectangle() {
+        let rotation: Basis2<f64> = Rotation2::from_angle(Rad(0.3));
+        let corners: Vec<_> = [(0.0, 0.0), (6.0, 0.0), (6.0, 2.0), (0.0, 2.0)]
+            .iter()
+            .map(|&(x, y)| point2(1.0, 1.0) + rotation.rotate_vector(vec2(x, y)))
+            .collect();
+        let path = Path::from_polygons(std::slice::from_ref(&corners));
+
+        let rect = path.minimum_area_rect().unwrap();
+        assert!((rect.area() - 12.0).abs() < 1e-9);
+        assert!((rect.angle().rem_euclid(PI / 2.0) - 0.3).abs() < 1e-9);
+        for corner in corners {
+            assert!(rect.corners().iter().any(|c| c.distance(corner) < 1e-9));
+        }
+        // the axis-aligned bounds are larger
+        assert!(path.bounds().unwrap().area() > 12.0);
+    }
+
+    #[test]
+    fn minimum_enclosing_circle() {
+        let square = Path::from_svg("M0 0 H4 V4 H0 Z").unwrap();
+        let circle = square.minimum_enclosing_circle().unwrap();
+        assert!(circle.center.distance(point2(2.0, 2.0)) < 1e-9);
+        assert!((circle.radius - 8f64.sqrt()).abs() < 1e-9);
+
+        // a circle's enclosing circle is itself, up to the curves' approximation
+        let path = Circle::new(point2(-1.0, 5.0), 3.0).to_path();
+        let circle = path.minimum_enclosing_circle().unwrap();
+        assert!(circle.center.distance(point2(-1.0, 5.0)) < 1e-3);
+        assert!((circle.radius - 3.0).abs() < 3e-3, "{}", circle.radius);
+
+        // a triangle with an obtuse angle is enclosed by the circle on its longest side
+        let triangle = Path::from_svg("M0 0 L10 0 L5 1 Z").unwrap();
+        let circle = triangle.minimum_enclosing_circle().unwrap();
+        assert!(circle.center.distance(point2(5.0, 0.0)) < 1e-9);
+        assert!((circle.radius - 5.0).abs() < 1e-9);
+        assert!(Path::new().minimum_enclosing_circle().is_none());
+    }
+}
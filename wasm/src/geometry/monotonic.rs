@@ -0,0 +1,232 @@
+// Monotonic curve splitting and edge lists for scanline rasterization
+
+use cgmath::Point2;
+
+use super::{path::Path, segment::Segment};
+
+// Piece of the outline that is monotonic in x and y, oriented downwards (start.y <= end.y)
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MonotoneEdge {
+    pub segment: Segment,
+    // +1 if the original segment runs towards +y, -1 if it was reversed
+    // (same convention as `polygons_winding_number`)
+    pub winding: i32,
+}
+
+impl MonotoneEdge {
+    pub fn top(&self) -> f64 {
+        self.segment.start().y
+    }
+
+    pub fn bottom(&self) -> f64 {
+        self.segment.end().y
+    }
+
+    // x coordinate where the edge crosses the horizontal line at `y` (clamped to the edge's ends)
+    pub fn x_at(&self, y: f64) -> f64 {
+        if y <= self.top() {
+            return self.segment.start().x;
+        }
+        if y >= self.bottom() {
+            return self.segment.end().x;
+        }
+        if let Segment::Line(p0, p1) = self.segment {
+            return p0.x + (p1.x - p0.x) * (y - p0.y) / (p1.y - p0.y);
+        }
+        let (mut low, mut high) = (0.0, 1.0);
+        for _ in 0..50 {
+            let middle = (low + high) / 2.0;
+            if self.segment.point_at(middle).y < y {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        self.segment.point_at((low + high) / 2.0).x
+    }
+}
+
+impl Segment {
+    // Splits the segment at its x and y extrema, in order along the segment.
+    // Consecutive pieces share their endpoints exactly.
+    pub fn split_monotonic(&self) -> Vec<Segment> {
+        let mut parameters = self.extrema(0);
+        parameters.extend(self.extrema(1));
+        parameters.sort_by(f64::total_cmp);
+        parameters.dedup();
+
+        let mut pieces = Vec::with_capacity(parameters.len() + 1);
+        let mut rest = *self;
+        let mut previous = 0.0;
+        for t in parameters {
+            let (head, tail) = rest.split((t - previous) / (1.0 - previous));
+            pieces.push(head);
+            rest = tail;
+            previous = t;
+        }
+        pieces.push(rest);
+        pieces
+    }
+
+    pub fn reversed(&self) -> Segment {
+        match *self {
+            Segment::Line(p0, p1) => Segment::Line(p1, p0),
+            Segment::Quad(p0, p1, p2) => Segment::Quad(p2, p1, p0),
+            Segment::Cubic(p0, p1, p2, p3) => Segment::Cubic(p3, p2, p1, p0),
+        }
+    }
+}
+
+impl Path {
+    // Monotone edges of the filled outline (open subpaths are implicitly closed), sorted by their top
+    // and then by their x coordinate there. Horizontal pieces are dropped since they cover no scanlines.
+    pub fn to_edge_list(&self) -> Vec<MonotoneEdge> {
+        let mut edges = Vec::new();
+        for subpath in self.subpaths() {
+            let mut segments = subpath.segments.clone();
+            if subpath.end() != subpath.start() {
+                segments.push(Segment::Line(subpath.end(), subpath.start()));
+            }
+            for piece in segments.iter().flat_map(Segment::split_monotonic) {
+                let (start, end): (Point2<f64>, Point2<f64>) = (piece.start(), piece.end());
+                if start.y < end.y {
+                    edges.push(MonotoneEdge {
+                        segment: piece,
+                        winding: 1,
+                    });
+                } else if start.y > end.y {
+                    edges.push(MonotoneEdge {
+                        segment: piece.reversed(),
+                        winding: -1,
+                    });
+                }
+            }
+        }
+        edges.sort_by(|a, b| {
+            a.top()
+                .total_cmp(&b.top())
+                .then(a.segment.start().x.total_cmp(&b.segment.start().x))
+        });
+        edges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::point2;
+
+    use super::*;
+
+    fn assert_monotonic(segment: &Segment) {
+        let samples: Vec<_> = (0..=100)
+            .map(|i| segment.point_at(i as f64 / 100.0))
+            .collect();
+        let (first, last) = (samples[0], samples[100]);
+        for pair in samples.windows(2) {
+            let (dx, dy) = (pair[1].x - pair[0].x, pair[1].y - pair[0].y);
+            assert!(
+                dx * (last.x - first.x) >= -1e-12,
+                "not monotonic in x: {:?}",
+                segment
+            );
+            assert!(
+                dy * (last.y - first.y) >= -1e-12,
+                "not monotonic in y: {:?}",
+                segment
+            );
+        }
+    }
+
+    #[test]
+    fn splits_curves_into_monotonic_pieces() {
+        for segment in [
+            // S-curve with two y extrema and one x extremum
+            Segment::Cubic(
+                point2(0.0, 0.0),
+                point2(10.0, 10.0),
+                point2(-5.0, -10.0),
+                point2(5.0, 0.0),
+            ),
+            Segment::Quad(point2(0.0, 0.0), point2(5.0, 10.0), point2(10.0, 0.0)),
+            Segment::Line(point2(0.0, 0.0), point2(3.0, -4.0)),
+        ] {
+            let pieces = segment.split_monotonic();
+            let expected = 1 + segment.extrema(0).len() + segment.extrema(1).len();
+            assert_eq!(pieces.len(), expected);
+            assert_eq!(pieces[0].start(), segment.start());
+            assert_eq!(pieces[pieces.len() - 1].end(), segment.end());
+            for pair in pieces.windows(2) {
+                assert_eq!(pair[0].end(), pair[1].start());
+            }
+            for piece in &pieces {
+                assert_monotonic(piece);
+            }
+        }
+    }
+
+    #[test]
+    fn edge_list_is_sorted_and_points_down() {
+        let path =
+            Path::from_svg("M0 0 C10 -10 20 20 30 0 L25 30 Q15 10 5 30 Z M40 5 L50 15 L40 15")
+                .unwrap();
+        let edges = path.to_edge_list();
+        for edge in &edges {
+            assert!(edge.top() < edge.bottom());
+            assert_monotonic(&edge.segment);
+        }
+        for pair in edges.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            assert!(
+                a.top() < b.top()
+                    || (a.top() == b.top() && a.segment.start().x <= b.segment.start().x)
+            );
+        }
+        // the open subpath is closed by a line; its horizontal edge is dropped
+        assert_eq!(
+            edges
+                .iter()
+                .filter(|edge| edge.segment.start().x >= 40.0)
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn edge_windings_follow_the_original_direction() {
+        // clockwise on screen (y down): down on the right, up on the left
+        let edges = Path::from_svg("M0 0 H10 V10 H0 Z").unwrap().to_edge_list();
+        assert_eq!(edges.len(), 2);
+        assert_eq!(
+            edges[0].segment,
+            Segment::Line(point2(0.0, 0.0), point2(0.0, 10.0))
+        );
+        assert_eq!(edges[0].winding, -1);
+        assert_eq!(
+            edges[1].segment,
+            Segment::Line(point2(10.0, 0.0), point2(10.0, 10.0))
+        );
+        assert_eq!(edges[1].winding, 1);
+        assert_eq!(edges[1].x_at(5.0), 10.0);
+    }
+
+    #[test]
+    fn edge_windings_give_the_winding_number() {
+        let path =
+            Path::from_svg("M0 0 C40 -10 40 50 0 40 Z M10 10 L10 30 L30 20 Z M5 5 H35 V35 H5 Z")
+                .unwrap();
+        let edges = path.to_edge_list();
+        for y in [2.5, 10.5, 20.5, 32.5] {
+            for x in [-1.0, 7.5, 12.5, 25.5, 33.0, 36.0] {
+                let point = point2(x, y);
+                // ray towards +x, like `polygons_winding_number`
+                let winding: i32 = edges
+                    .iter()
+                    .filter(|edge| edge.top() <= y && y < edge.bottom() && edge.x_at(y) > x)
+                    .map(|edge| edge.winding)
+                    .sum();
+                assert_eq!(winding, path.winding_number(point), "at {:?}", point);
+            }
+        }
+    }
+}
@@ -0,0 +1,262 @@
+// Growing and shrinking filled regions by a distance
+
+use cgmath::{vec2, InnerSpace, Point2, Vector2};
+
+use super::{fill_rule::FillRule, path::Path, self_intersection::resolve_polygons};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct OffsetOptions {
+    pub join: LineJoin,
+    // miter joins longer than `miter_limit * distance` fall back to bevels (like SVG's stroke-miterlimit)
+    pub miter_limit: f64,
+    // max distance between round joins and their flattened arcs
+    pub tolerance: f64,
+    // fill rule used to interpret the input path
+    pub fill_rule: FillRule,
+}
+
+impl Default for OffsetOptions {
+    fn default() -> Self {
+        OffsetOptions {
+            join: LineJoin::Miter,
+            miter_limit: 4.0,
+            tolerance: 0.1,
+            fill_rule: FillRule::NonZero,
+        }
+    }
+}
+
+impl Path {
+    // Grows the filled region by `distance` (or shrinks it if negative).
+    // The result is a set of simple loops with the same orientation as `resolve_self_intersections`.
+    // Curves are flattened in the result.
+    pub fn offset(&self, distance: f64, options: &OffsetOptions) -> Path {
+        let fill_rule = options.fill_rule;
+        let loops = resolve_polygons(&self.to_polygons(), |winding| fill_rule.is_inside(winding));
+        if distance == 0.0 || !distance.is_finite() {
+            return Path::from_polygons(&loops);
+        }
+
+        let contours: Vec<Vec<Point2<f64>>> = loops
+            .iter()
+            .map(|polygon| offset_polygon(polygon, distance, options))
+            .collect();
+        // overlaps between grown loops and the inverted parts of shrunk loops are removed here
+        Path::from_polygons(&resolve_polygons(&contours, |winding| winding > 0))
+    }
+}
+
+fn cross(a: Vector2<f64>, b: Vector2<f64>) -> f64 {
+    a.x * b.y - a.y * b.x
+}
+
+// Offsets the polygon's edges to their right (away from a region on their left) and joins them.
+// The raw contour may self-intersect; inside corners are routed through the original vertex
+// so that the loops they form cancel out when resolved.
+fn offset_polygon(
+    polygon: &[Point2<f64>],
+    distance: f64,
+    options: &OffsetOptions,
+) -> Vec<Point2<f64>> {
+    let mut points: Vec<Point2<f64>> = polygon.to_vec();
+    points.dedup();
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    let n = points.len();
+    if n < 3 {
+        return Vec::new();
+    }
+
+    // unit right-hand normals of the edges (edge i goes from point i to point i + 1)
+    let normals: Vec<Vector2<f64>> = (0..n)
+        .map(|i| {
+            let direction = (points[(i + 1) % n] - points[i]).normalize();
+            vec2(direction.y, -direction.x)
+        })
+        .collect();
+
+    let mut contour = Vec::new();
+    for i in 0..n {
+        let vertex = points[i];
+        let incoming = normals[(i + n - 1) % n];
+        let outgoing = normals[i];
+        let start = vertex + incoming * distance;
+        let end = vertex + outgoing * distance;
+
+        // the turn from the incoming to the outgoing normal has the same sign as the turn between the edges
+        let turn = cross(incoming, outgoing);
+        if turn * distance > 0.0 {
+            append_join(&mut contour, vertex, incoming, outgoing, distance, options);
+        } else if turn * distance < 0.0 {
+            contour.push(start);
+            contour.push(vertex);
+            contour.push(end);
+        } else {
+            contour.push(end);
+        }
+    }
+    contour
+}
+
+fn append_join(
+    contour: &mut Vec<Point2<f64>>,
+    vertex: Point2<f64>,
+    incoming: Vector2<f64>,
+    outgoing: Vector2<f64>,
+    distance: f64,
+    options: &OffsetOptions,
+) {
+    let start = vertex + incoming * distance;
+    let end = vertex + outgoing * distance;
+    match options.join {
+        LineJoin::Miter => {
+            let cos = incoming.dot(outgoing);
+            // the miter tip is at 1 / cos(angle / 2) times the distance from the vertex
+            if 1.0 + cos > 2.0 / (options.miter_limit * options.miter_limit) {
+                contour.push(vertex + (incoming + outgoing) * (distance / (1.0 + cos)));
+            } else {
+                contour.push(start);
+                contour.push(end);
+            }
+        }
+        LineJoin::Bevel => {
+            contour.push(start);
+            contour.push(end);
+        }
+        LineJoin::Round => {
+            let radius = distance.abs();
+            let angle = cross(incoming, outgoing).atan2(incoming.dot(outgoing));
+            let max_step = if options.tolerance > 0.0 && options.tolerance < radius {
+                2.0 * (1.0 - options.tolerance / radius).acos()
+            } else {
+                std::f64::consts::FRAC_PI_2
+            };
+            let steps = (angle.abs() / max_step).ceil().max(1.0) as usize;
+            contour.push(start);
+            for k in 1..steps {
+                let (sin, cos) = (angle * k as f64 / steps as f64).sin_cos();
+                let normal = vec2(
+                    incoming.x * cos - incoming.y * sin,
+                    incoming.x * sin + incoming.y * cos,
+                );
+                contour.push(vertex + normal * distance);
+            }
+            contour.push(end);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use cgmath::point2;
+
+    use super::*;
+
+    fn square() -> Path {
+        Path::from_svg("M0 0 H10 V10 H0 Z").unwrap()
+    }
+
+    fn area(path: &Path) -> f64 {
+        path.to_polygons()
+            .iter()
+            .map(|polygon| {
+                let n = polygon.len();
+                (0..n)
+                    .map(|i| {
+                        let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+                        a.x * b.y - b.x * a.y
+                    })
+                    .sum::<f64>()
+                    / 2.0
+            })
+            .sum::<f64>()
+            .abs()
+    }
+
+    fn assert_bounds(path: &Path, min: f64, max: f64) {
+        let bounds = path.bounds().unwrap();
+        assert!(
+            (bounds.min - point2(min, min)).magnitude() < 1e-9,
+            "{:?}",
+            bounds
+        );
+        assert!(
+            (bounds.max - point2(max, max)).magnitude() < 1e-9,
+            "{:?}",
+            bounds
+        );
+    }
+
+    fn options(join: LineJoin) -> OffsetOptions {
+        OffsetOptions {
+            join,
+            ..OffsetOptions::default()
+        }
+    }
+
+    #[test]
+    fn grows_a_square_with_each_join() {
+        let miter = square().offset(1.0, &options(LineJoin::Miter));
+        assert_bounds(&miter, -1.0, 11.0);
+        assert!((area(&miter) - 144.0).abs() < 1e-9);
+
+        // each corner loses a triangle with legs of 1
+        let bevel = square().offset(1.0, &options(LineJoin::Bevel));
+        assert_bounds(&bevel, -1.0, 11.0);
+        assert!((area(&bevel) - 142.0).abs() < 1e-9);
+
+        // quarter circles at the corners, flattened inside the true arcs
+        // (each chord cuts off less than `tolerance` times its length)
+        let round = square().offset(1.0, &options(LineJoin::Round));
+        assert_bounds(&round, -1.0, 11.0);
+        let exact = 140.0 + PI;
+        let tolerance = OffsetOptions::default().tolerance;
+        assert!(area(&round) < exact && area(&round) > exact - tolerance * 2.0 * PI);
+        for polygon in round.to_polygons() {
+            for point in polygon {
+                let nearest = point2(point.x.clamp(0.0, 10.0), point.y.clamp(0.0, 10.0));
+                assert!(((point - nearest).magnitude() - 1.0).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn long_miters_fall_back_to_bevels() {
+        // a right angle's miter is sqrt(2) times the distance
+        let mut options = options(LineJoin::Miter);
+        options.miter_limit = 1.4;
+        assert!((area(&square().offset(1.0, &options)) - 142.0).abs() < 1e-9);
+        options.miter_limit = 1.5;
+        assert!((area(&square().offset(1.0, &options)) - 144.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn shrinks_a_square() {
+        for join in [LineJoin::Miter, LineJoin::Round, LineJoin::Bevel] {
+            let path = square().offset(-1.0, &options(join));
+            assert_bounds(&path, 1.0, 9.0);
+            assert!((area(&path) - 64.0).abs() < 1e-9, "{:?}", join);
+
+            // shrinking past the middle leaves nothing
+            assert!(square().offset(-6.0, &options(join)).bounds().is_none());
+        }
+    }
+
+    #[test]
+    fn zero_distance_keeps_the_region() {
+        let path = square().offset(0.0, &OffsetOptions::default());
+        assert_bounds(&path, 0.0, 10.0);
+        assert!((area(&path) - 100.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,61 @@
+use cgmath::{vec2, Point2, Vector2};
+
+use super::path::Path;
+
+// Rectangle rotated so that its first side runs along `axis`
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrientedRect {
+    pub center: Point2<f64>,
+    // unit direction of the first side
+    pub axis: Vector2<f64>,
+    // half the side lengths along `axis` and perpendicular to it
+    pub half_extents: Vector2<f64>,
+}
+
+impl OrientedRect {
+    pub fn new(
+        center: Point2<f64>,
+        axis: Vector2<f64>,
+        half_extents: Vector2<f64>,
+    ) -> OrientedRect {
+        OrientedRect {
+            center,
+            axis,
+            half_extents,
+        }
+    }
+
+    pub fn width(&self) -> f64 {
+        2.0 * self.half_extents.x
+    }
+
+    pub fn height(&self) -> f64 {
+        2.0 * self.half_extents.y
+    }
+
+    pub fn area(&self) -> f64 {
+        self.width() * self.height()
+    }
+
+    // rotation of the axis from the x axis in radians
+    pub fn angle(&self) -> f64 {
+        self.axis.y.atan2(self.axis.x)
+    }
+
+    // corners in the same order as `Rect::corners` once rotated into the rectangle's frame
+    pub fn corners(&self) -> [Point2<f64>; 4] {
+        let u = self.axis * self.half_extents.x;
+        let v = vec2(-self.axis.y, self.axis.x) * self.half_extents.y;
+        [
+            self.center - u - v,
+            self.center + u - v,
+            self.center + u + v,
+            self.center - u + v,
+        ]
+    }
+
+    pub fn to_path(&self) -> Path {
+        Path::from_polygons(&[self.corners().to_vec()])
+    }
+}
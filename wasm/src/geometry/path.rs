@@ -1,54 +1,345 @@
-use cgmath::{point2, Point2};
+use cgmath::{point2, Point2, Vector2};
 
 use super::{
+    error::PathError,
+    fill_rule::FillRule,
+    rect::Rect,
     segment::Segment,
     subdivision::{subdivideCubic, subdivideQuad},
 };
 
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "Vec<PathCommand>", try_from = "Vec<PathCommand>")
+)]
 pub struct Path {
     commands: Vec<PathCommand>,
+    // builder state used by relative and smooth commands
+    subpath_start: Point2<f64>,
+    current: Point2<f64>,
+    last_control: LastControl,
+}
+
+// The control point of the previous curve, reflected by smooth commands.
+// Like SVG, a smooth quad only reflects a previous quad and a smooth cubic only a previous cubic.
+#[derive(Clone, Copy, Debug)]
+enum LastControl {
+    None,
+    Quad(Point2<f64>),
+    Cubic(Point2<f64>),
 }
 
 impl Path {
     pub fn new() -> Path {
         Path {
             commands: Vec::new(),
+            subpath_start: point2(0.0, 0.0),
+            current: point2(0.0, 0.0),
+            last_control: LastControl::None,
         }
     }
 
     pub fn move_to(&mut self, point: Point2<f64>) {
         self.commands.push(PathCommand::MoveTo(point));
+        self.subpath_start = point;
+        self.current = point;
+        self.last_control = LastControl::None;
     }
 
     pub fn line_to(&mut self, point: Point2<f64>) {
         self.commands.push(PathCommand::LineTo(point));
+        self.current = point;
+        self.last_control = LastControl::None;
     }
 
     pub fn quad_to(&mut self, control: Point2<f64>, point: Point2<f64>) {
         self.commands.push(PathCommand::QuadTo(control, point));
+        self.current = point;
+        self.last_control = LastControl::Quad(control);
     }
 
     pub fn cubic_to(&mut self, control1: Point2<f64>, control2: Point2<f64>, point: Point2<f64>) {
         self.commands
             .push(PathCommand::CubicTo(control1, control2, point));
+        self.current = point;
+        self.last_control = LastControl::Cubic(control2);
     }
 
     pub fn close(&mut self) {
         self.commands.push(PathCommand::Close);
+        // the next command continues from the start of the closed subpath
+        self.current = self.subpath_start;
+        self.last_control = LastControl::None;
+    }
+
+    pub fn horizontal_line_to(&mut self, x: f64) {
+        self.line_to(point2(x, self.current.y));
+    }
+
+    pub fn vertical_line_to(&mut self, y: f64) {
+        self.line_to(point2(self.current.x, y));
+    }
+
+    // Quadratic curve whose control point is the reflection of the previous quad's control point
+    // (or the current point if the previous command was not a quad)
+    pub fn smooth_quad_to(&mut self, point: Point2<f64>) {
+        let control = match self.last_control {
+            LastControl::Quad(control) => self.current + (self.current - control),
+            _ => self.current,
+        };
+        self.quad_to(control, point);
+    }
+
+    // Cubic curve whose first control point is the reflection of the previous cubic's second control point
+    // (or the current point if the previous command was not a cubic)
+    pub fn smooth_cubic_to(&mut self, control2: Point2<f64>, point: Point2<f64>) {
+        let control1 = match self.last_control {
+            LastControl::Cubic(control) => self.current + (self.current - control),
+            _ => self.current,
+        };
+        self.cubic_to(control1, control2, point);
+    }
+
+    pub fn relative_move_to(&mut self, to: Vector2<f64>) {
+        self.move_to(self.current + to);
+    }
+
+    pub fn relative_line_to(&mut self, to: Vector2<f64>) {
+        self.line_to(self.current + to);
+    }
+
+    pub fn relative_quad_to(&mut self, control: Vector2<f64>, to: Vector2<f64>) {
+        self.quad_to(self.current + control, self.current + to);
+    }
+
+    pub fn relative_cubic_to(
+        &mut self,
+        control1: Vector2<f64>,
+        control2: Vector2<f64>,
+        to: Vector2<f64>,
+    ) {
+        self.cubic_to(
+            self.current + control1,
+            self.current + control2,
+            self.current + to,
+        );
+    }
+
+    pub fn relative_horizontal_line_to(&mut self, dx: f64) {
+        self.horizontal_line_to(self.current.x + dx);
+    }
+
+    pub fn relative_vertical_line_to(&mut self, dy: f64) {
+        self.vertical_line_to(self.current.y + dy);
+    }
+
+    pub fn relative_smooth_quad_to(&mut self, to: Vector2<f64>) {
+        self.smooth_quad_to(self.current + to);
+    }
+
+    pub fn relative_smooth_cubic_to(&mut self, control2: Vector2<f64>, to: Vector2<f64>) {
+        self.smooth_cubic_to(self.current + control2, self.current + to);
+    }
+
+    // Elliptical arc like SVG's A command, approximated with one cubic per quarter turn.
+    // `x_axis_rotation` is in degrees; out-of-range radii are scaled up like in SVG.
+    pub fn arc_to(
+        &mut self,
+        radii: Vector2<f64>,
+        x_axis_rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        point: Point2<f64>,
+    ) {
+        let start = self.current;
+        if start == point {
+            return;
+        }
+        let (mut rx, mut ry) = (radii.x.abs(), radii.y.abs());
+        if rx == 0.0 || ry == 0.0 {
+            self.line_to(point);
+            return;
+        }
+
+        // center parameterization (SVG implementation notes, F.6.5)
+        let (sin, cos) = x_axis_rotation.to_radians().sin_cos();
+        let rotate = |v: Vector2<f64>| Vector2::new(cos * v.x - sin * v.y, sin * v.x + cos * v.y);
+        let half = (start - point) / 2.0;
+        let p = Vector2::new(cos * half.x + sin * half.y, -sin * half.x + cos * half.y);
+        let lambda = (p.x / rx).powi(2) + (p.y / ry).powi(2);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+        let numerator = (rx * ry).powi(2) - (rx * p.y).powi(2) - (ry * p.x).powi(2);
+        let denominator = (rx * p.y).powi(2) + (ry * p.x).powi(2);
+        let mut coefficient = (numerator / denominator).max(0.0).sqrt();
+        if large_arc == sweep {
+            coefficient = -coefficient;
+        }
+        let center_prime = Vector2::new(coefficient * rx * p.y / ry, -coefficient * ry * p.x / rx);
+        let center = point + half + rotate(center_prime);
+
+        let angle = |v: Vector2<f64>| v.y.atan2(v.x);
+        let start_angle = angle(Vector2::new(
+            (p.x - center_prime.x) / rx,
+            (p.y - center_prime.y) / ry,
+        ));
+        let end_angle = angle(Vector2::new(
+            (-p.x - center_prime.x) / rx,
+            (-p.y - center_prime.y) / ry,
+        ));
+        let mut sweep_angle = end_angle - start_angle;
+        if sweep && sweep_angle < 0.0 {
+            sweep_angle += std::f64::consts::TAU;
+        } else if !sweep && sweep_angle > 0.0 {
+            sweep_angle -= std::f64::consts::TAU;
+        }
+
+        let pieces = (sweep_angle.abs() / std::f64::consts::FRAC_PI_2 - 1e-9)
+            .ceil()
+            .max(1.0) as usize;
+        let step = sweep_angle / pieces as f64;
+        let handle = 4.0 / 3.0 * (step / 4.0).tan();
+        let point_at = |t: f64| center + rotate(Vector2::new(rx * t.cos(), ry * t.sin()));
+        let tangent_at = |t: f64| rotate(Vector2::new(-rx * t.sin(), ry * t.cos()));
+        for i in 0..pieces {
+            let t0 = start_angle + step * i as f64;
+            let t1 = t0 + step;
+            let end = if i + 1 == pieces { point } else { point_at(t1) };
+            self.cubic_to(
+                point_at(t0) + tangent_at(t0) * handle,
+                point_at(t1) - tangent_at(t1) * handle,
+                end,
+            );
+        }
+        // smooth curves after an arc don't reflect its control points
+        self.last_control = LastControl::None;
+    }
+
+    pub fn relative_arc_to(
+        &mut self,
+        radii: Vector2<f64>,
+        x_axis_rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        to: Vector2<f64>,
+    ) {
+        self.arc_to(radii, x_axis_rotation, large_arc, sweep, self.current + to);
+    }
+
+    pub fn current_point(&self) -> Point2<f64> {
+        self.current
+    }
+
+    pub fn push(&mut self, command: PathCommand) {
+        match command {
+            PathCommand::MoveTo(point) => self.move_to(point),
+            PathCommand::LineTo(point) => self.line_to(point),
+            PathCommand::QuadTo(control, point) => self.quad_to(control, point),
+            PathCommand::CubicTo(control1, control2, point) => {
+                self.cubic_to(control1, control2, point)
+            }
+            PathCommand::Close => self.close(),
+        }
+    }
+
+    // Closed polygonal subpaths
+    pub fn from_polygons(polygons: &[Vec<Point2<f64>>]) -> Path {
+        let mut path = Path::new();
+        for polygon in polygons {
+            if let Some((first, rest)) = polygon.split_first() {
+                path.move_to(*first);
+                for point in rest {
+                    path.line_to(*point);
+                }
+                path.close();
+            }
+        }
+        path
+    }
+
+    // Appends the segment, assuming it starts at the current point
+    pub fn push_segment(&mut self, segment: &Segment) {
+        match *segment {
+            Segment::Line(_, p1) => self.line_to(p1),
+            Segment::Quad(_, p1, p2) => self.quad_to(p1, p2),
+            Segment::Cubic(_, p1, p2, p3) => self.cubic_to(p1, p2, p3),
+        }
+    }
+
+    pub fn from_commands(commands: Vec<PathCommand>) -> Result<Path, PathError> {
+        let mut path = Path::new();
+        let mut has_move_to = false;
+        for (index, command) in commands.into_iter().enumerate() {
+            command.validate(index, has_move_to)?;
+            has_move_to |= command.is_move_to();
+            path.push(command);
+        }
+        Ok(path)
+    }
+
+    // Checks that all coordinates are finite and that every subpath starts with a MoveTo
+    pub fn validate(&self) -> Result<(), PathError> {
+        let mut has_move_to = false;
+        for (index, command) in self.commands.iter().enumerate() {
+            command.validate(index, has_move_to)?;
+            has_move_to |= command.is_move_to();
+        }
+        Ok(())
+    }
+
+    // Removes zero-length segments, repeated MoveTos and empty subpaths
+    pub fn sanitize(&mut self) {
+        let mut result = Path::new();
+        let mut pending_move: Option<Point2<f64>> = None;
+
+        for command in &self.commands {
+            let current = pending_move.unwrap_or(result.current);
+            let is_degenerate = match *command {
+                PathCommand::MoveTo(point) => {
+                    pending_move = Some(point);
+                    continue;
+                }
+                PathCommand::LineTo(point) => point == current,
+                PathCommand::QuadTo(control, point) => control == current && point == current,
+                PathCommand::CubicTo(control1, control2, point) => {
+                    control1 == current && control2 == current && point == current
+                }
+                PathCommand::Close => {
+                    // closing an empty subpath or closing twice has no effect
+                    pending_move.is_some()
+                        || matches!(result.commands.last(), None | Some(PathCommand::Close))
+                }
+            };
+            if is_degenerate {
+                continue;
+            }
+            if let Some(point) = pending_move.take() {
+                result.move_to(point);
+            }
+            result.push(*command);
+        }
+
+        *self = result;
     }
 
     pub fn commands(&self) -> &[PathCommand] {
         &self.commands
     }
 
-    // TODO: use iterator
+    // Segments of all subpaths, including the closing lines of closed subpaths
     pub fn to_segments(&self) -> Vec<Segment> {
+        let mut subpath_start = point2(0.0, 0.0);
         let mut start = point2(0.0, 0.0);
         let mut segments = Vec::new();
 
         for command in &self.commands {
             match command {
                 PathCommand::MoveTo(point) => {
+                    subpath_start = *point;
                     start = *point;
                 }
                 PathCommand::LineTo(point) => {
@@ -64,21 +355,109 @@ impl Path {
                     start = *point;
                 }
                 PathCommand::Close => {
-                    // TODO
+                    if start != subpath_start {
+                        segments.push(Segment::Line(start, subpath_start));
+                    }
+                    start = subpath_start;
                 }
             }
         }
 
-        return segments;
+        segments
+    }
+
+    pub fn subpaths(&self) -> Vec<Subpath> {
+        let mut subpaths = Vec::new();
+        let mut segments = Vec::new();
+        let mut subpath_start = point2(0.0, 0.0);
+        let mut start = point2(0.0, 0.0);
+
+        for command in &self.commands {
+            match *command {
+                PathCommand::MoveTo(point) => {
+                    if !segments.is_empty() {
+                        subpaths.push(Subpath {
+                            segments: std::mem::take(&mut segments),
+                            closed: false,
+                        });
+                    }
+                    subpath_start = point;
+                    start = point;
+                }
+                PathCommand::LineTo(point) => {
+                    segments.push(Segment::Line(start, point));
+                    start = point;
+                }
+                PathCommand::QuadTo(control, point) => {
+                    segments.push(Segment::Quad(start, control, point));
+                    start = point;
+                }
+                PathCommand::CubicTo(control1, control2, point) => {
+                    segments.push(Segment::Cubic(start, control1, control2, point));
+                    start = point;
+                }
+                PathCommand::Close => {
+                    if !segments.is_empty() {
+                        subpaths.push(Subpath {
+                            segments: std::mem::take(&mut segments),
+                            closed: true,
+                        });
+                    }
+                    start = subpath_start;
+                }
+            }
+        }
+        if !segments.is_empty() {
+            subpaths.push(Subpath {
+                segments,
+                closed: false,
+            });
+        }
+
+        subpaths
+    }
+
+    // Flattened contours for filling (open subpaths are implicitly closed)
+    pub fn to_polygons(&self) -> Vec<Vec<Point2<f64>>> {
+        self.subpaths()
+            .iter()
+            .map(|subpath| {
+                let mut points = vec![subpath.start()];
+                for segment in &subpath.segments {
+                    points.extend(segment.flatten());
+                }
+                if points.len() > 1 && points.first() == points.last() {
+                    points.pop();
+                }
+                points
+            })
+            .collect()
+    }
+
+    pub fn bounds(&self) -> Option<Rect> {
+        self.to_segments()
+            .iter()
+            .map(Segment::bounds)
+            .reduce(|a, b| a.union(&b))
+    }
+
+    pub fn winding_number(&self, point: Point2<f64>) -> i32 {
+        polygons_winding_number(&self.to_polygons(), point)
+    }
+
+    pub fn contains_point(&self, point: Point2<f64>, fill_rule: FillRule) -> bool {
+        fill_rule.is_inside(self.winding_number(point))
     }
 
     pub fn subdivide(&self) -> Vec<Point2<f64>> {
+        let mut subpath_start = point2(0.0, 0.0);
         let mut start = point2(0.0, 0.0);
         let mut points = Vec::new();
 
         for command in &self.commands {
             match command {
                 PathCommand::MoveTo(point) => {
+                    subpath_start = *point;
                     start = *point;
                     points.push(*point);
                 }
@@ -96,6 +475,7 @@ impl Path {
                 }
                 PathCommand::Close => {
                     // TODO
+                    start = subpath_start;
                 }
             }
         }
@@ -104,6 +484,69 @@ impl Path {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct Subpath {
+    pub segments: Vec<Segment>,
+    pub closed: bool,
+}
+
+impl Subpath {
+    pub fn start(&self) -> Point2<f64> {
+        self.segments[0].start()
+    }
+
+    pub fn end(&self) -> Point2<f64> {
+        self.segments[self.segments.len() - 1].end()
+    }
+}
+
+// Winding number of closed polygons around a point (counter-clockwise in y-up coordinates is positive)
+pub fn polygons_winding_number(polygons: &[Vec<Point2<f64>>], point: Point2<f64>) -> i32 {
+    let mut winding = 0;
+    for polygon in polygons {
+        for i in 0..polygon.len() {
+            winding += edge_winding(polygon[i], polygon[(i + 1) % polygon.len()], point);
+        }
+    }
+    winding
+}
+
+// Contribution of the edge a-b to the winding number around a point: ±1 if the edge crosses
+// the horizontal ray to the right of the point (including a but not b), 0 otherwise
+pub fn edge_winding(a: Point2<f64>, b: Point2<f64>, point: Point2<f64>) -> i32 {
+    let side = (b.x - a.x) * (point.y - a.y) - (point.x - a.x) * (b.y - a.y);
+    if a.y <= point.y {
+        if b.y > point.y && side > 0.0 {
+            return 1;
+        }
+    } else if b.y <= point.y && side < 0.0 {
+        return -1;
+    }
+    0
+}
+
+impl From<Path> for Vec<PathCommand> {
+    fn from(path: Path) -> Self {
+        path.commands
+    }
+}
+
+impl Default for Path {
+    fn default() -> Self {
+        Path::new()
+    }
+}
+
+impl TryFrom<Vec<PathCommand>> for Path {
+    type Error = PathError;
+
+    fn try_from(commands: Vec<PathCommand>) -> Result<Self, Self::Error> {
+        Path::from_commands(commands)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PathCommand {
     MoveTo(Point2<f64>),
     LineTo(Point2<f64>),
@@ -111,3 +554,248 @@ pub enum PathCommand {
     CubicTo(Point2<f64>, Point2<f64>, Point2<f64>),
     Close,
 }
+
+impl PathCommand {
+    pub fn is_move_to(&self) -> bool {
+        matches!(self, PathCommand::MoveTo(_))
+    }
+
+    pub fn is_finite(&self) -> bool {
+        let is_finite = |point: &Point2<f64>| point.x.is_finite() && point.y.is_finite();
+        match self {
+            PathCommand::MoveTo(point) | PathCommand::LineTo(point) => is_finite(point),
+            PathCommand::QuadTo(control, point) => is_finite(control) && is_finite(point),
+            PathCommand::CubicTo(control1, control2, point) => {
+                is_finite(control1) && is_finite(control2) && is_finite(point)
+            }
+            PathCommand::Close => true,
+        }
+    }
+
+    // `has_move_to` tells whether a MoveTo precedes this command at `index`
+    pub fn validate(&self, index: usize, has_move_to: bool) -> Result<(), PathError> {
+        if !self.is_finite() {
+            return Err(PathError::NonFiniteCoordinate {
+                command_index: index,
+            });
+        }
+        if !has_move_to && !self.is_move_to() {
+            return Err(PathError::MissingMoveTo {
+                command_index: index,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::vec2;
+
+    use super::*;
+
+    fn path(build: impl FnOnce(&mut Path)) -> Path {
+        let mut path = Path::new();
+        build(&mut path);
+        path
+    }
+
+    #[test]
+    fn horizontal_and_vertical_lines_keep_the_other_coordinate() {
+        let path = path(|path| {
+            path.move_to(point2(1.0, 2.0));
+            path.horizontal_line_to(5.0);
+            path.vertical_line_to(7.0);
+            path.relative_horizontal_line_to(-2.0);
+            path.relative_vertical_line_to(-1.0);
+        });
+        assert_eq!(
+            path.commands(),
+            &[
+                PathCommand::MoveTo(point2(1.0, 2.0)),
+                PathCommand::LineTo(point2(5.0, 2.0)),
+                PathCommand::LineTo(point2(5.0, 7.0)),
+                PathCommand::LineTo(point2(3.0, 7.0)),
+                PathCommand::LineTo(point2(3.0, 6.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn relative_commands_start_at_the_current_point() {
+        let path = path(|path| {
+            path.move_to(point2(10.0, 10.0));
+            path.relative_line_to(vec2(5.0, 0.0));
+            path.relative_quad_to(vec2(0.0, 5.0), vec2(-5.0, 5.0));
+            path.relative_cubic_to(vec2(1.0, 0.0), vec2(2.0, 1.0), vec2(3.0, 3.0));
+            path.relative_move_to(vec2(-1.0, -1.0));
+        });
+        assert_eq!(
+            path.commands(),
+            &[
+                PathCommand::MoveTo(point2(10.0, 10.0)),
+                PathCommand::LineTo(point2(15.0, 10.0)),
+                PathCommand::QuadTo(point2(15.0, 15.0), point2(10.0, 15.0)),
+                PathCommand::CubicTo(point2(11.0, 15.0), point2(12.0, 16.0), point2(13.0, 18.0)),
+                PathCommand::MoveTo(point2(12.0, 17.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn relative_commands_after_close_start_at_the_subpath_start() {
+        let path = path(|path| {
+            path.move_to(point2(1.0, 1.0));
+            path.line_to(point2(4.0, 1.0));
+            path.line_to(point2(4.0, 4.0));
+            path.close();
+            path.relative_line_to(vec2(0.0, 2.0));
+        });
+        assert_eq!(path.current_point(), point2(1.0, 3.0));
+        assert_eq!(
+            path.commands().last(),
+            Some(&PathCommand::LineTo(point2(1.0, 3.0)))
+        );
+    }
+
+    #[test]
+    fn smooth_cubic_reflects_the_previous_cubic_control_point() {
+        let path = path(|path| {
+            path.move_to(point2(0.0, 0.0));
+            path.cubic_to(point2(0.0, 1.0), point2(2.0, 3.0), point2(3.0, 3.0));
+            path.smooth_cubic_to(point2(6.0, 1.0), point2(6.0, 0.0));
+            path.relative_smooth_cubic_to(vec2(1.0, -2.0), vec2(2.0, -2.0));
+        });
+        assert_eq!(
+            path.commands()[2],
+            PathCommand::CubicTo(point2(4.0, 3.0), point2(6.0, 1.0), point2(6.0, 0.0))
+        );
+        assert_eq!(
+            path.commands()[3],
+            PathCommand::CubicTo(point2(6.0, -1.0), point2(7.0, -2.0), point2(8.0, -2.0))
+        );
+    }
+
+    #[test]
+    fn smooth_quad_reflects_the_previous_quad_control_point() {
+        let path = path(|path| {
+            path.move_to(point2(0.0, 0.0));
+            path.quad_to(point2(1.0, 2.0), point2(2.0, 0.0));
+            path.smooth_quad_to(point2(4.0, 0.0));
+            path.relative_smooth_quad_to(vec2(2.0, 0.0));
+        });
+        assert_eq!(
+            &path.commands()[2..],
+            &[
+                PathCommand::QuadTo(point2(3.0, -2.0), point2(4.0, 0.0)),
+                PathCommand::QuadTo(point2(5.0, 2.0), point2(6.0, 0.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn smooth_curves_only_reflect_curves_of_the_same_kind() {
+        let path = path(|path| {
+            path.move_to(point2(0.0, 0.0));
+            path.quad_to(point2(1.0, 2.0), point2(2.0, 0.0));
+            // the previous curve is a quad, so the first control point is the current point
+            path.smooth_cubic_to(point2(3.0, 1.0), point2(4.0, 0.0));
+            path.line_to(point2(5.0, 0.0));
+            path.smooth_quad_to(point2(6.0, 0.0));
+        });
+        assert_eq!(
+            path.commands()[2],
+            PathCommand::CubicTo(point2(2.0, 0.0), point2(3.0, 1.0), point2(4.0, 0.0))
+        );
+        assert_eq!(
+            path.commands()[4],
+            PathCommand::QuadTo(point2(5.0, 0.0), point2(6.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn segments_include_the_closing_line() {
+        let triangle = path(|path| {
+            path.move_to(point2(0.0, 0.0));
+            path.line_to(point2(4.0, 0.0));
+            path.line_to(point2(0.0, 3.0));
+            path.close();
+        });
+        assert_eq!(
+            triangle.to_segments(),
+            vec![
+                Segment::Line(point2(0.0, 0.0), point2(4.0, 0.0)),
+                Segment::Line(point2(4.0, 0.0), point2(0.0, 3.0)),
+                Segment::Line(point2(0.0, 3.0), point2(0.0, 0.0)),
+            ]
+        );
+
+        // no zero-length closing line when the subpath already ends at its start
+        let square = Path::from_svg("M0 0 H2 V2 H0 V0 Z").unwrap();
+        assert_eq!(square.to_segments().len(), 4);
+    }
+
+    #[test]
+    fn validate_reports_the_first_invalid_command() {
+        let mut path = Path::new();
+        path.line_to(point2(1.0, 1.0));
+        assert_eq!(
+            path.validate(),
+            Err(PathError::MissingMoveTo { command_index: 0 })
+        );
+
+        let mut path = Path::new();
+        path.move_to(point2(0.0, 0.0));
+        path.line_to(point2(1.0, 1.0));
+        path.cubic_to(
+            point2(1.0, 2.0),
+            point2(f64::INFINITY, 2.0),
+            point2(3.0, 3.0),
+        );
+        assert_eq!(
+            path.validate(),
+            Err(PathError::NonFiniteCoordinate { command_index: 2 })
+        );
+
+        assert_eq!(
+            Path::from_svg("M0 0 L1 1 Z M2 2 L3 3").unwrap().validate(),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn sanitize_removes_degenerate_commands() {
+        let mut path = Path::from_commands(vec![
+            // empty subpath
+            PathCommand::MoveTo(point2(9.0, 9.0)),
+            PathCommand::Close,
+            // only the last of repeated MoveTos counts
+            PathCommand::MoveTo(point2(5.0, 5.0)),
+            PathCommand::MoveTo(point2(0.0, 0.0)),
+            PathCommand::LineTo(point2(0.0, 0.0)),
+            PathCommand::LineTo(point2(2.0, 0.0)),
+            PathCommand::QuadTo(point2(2.0, 0.0), point2(2.0, 0.0)),
+            PathCommand::CubicTo(point2(2.0, 0.0), point2(2.0, 0.0), point2(2.0, 0.0)),
+            // a curve with a single distinct control point still bends
+            PathCommand::QuadTo(point2(3.0, 1.0), point2(2.0, 0.0)),
+            PathCommand::LineTo(point2(2.0, 2.0)),
+            PathCommand::Close,
+            PathCommand::Close,
+            // trailing MoveTo without segments
+            PathCommand::MoveTo(point2(7.0, 7.0)),
+        ])
+        .unwrap();
+        path.sanitize();
+
+        assert_eq!(
+            path.commands(),
+            &[
+                PathCommand::MoveTo(point2(0.0, 0.0)),
+                PathCommand::LineTo(point2(2.0, 0.0)),
+                PathCommand::QuadTo(point2(3.0, 1.0), point2(2.0, 0.0)),
+                PathCommand::LineTo(point2(2.0, 2.0)),
+                PathCommand::Close,
+            ]
+        );
+    }
+}
@@ -0,0 +1,55 @@
+// Real roots of low-degree polynomials
+
+// Roots of a*t + b
+pub fn solve_linear(a: f64, b: f64) -> Vec<f64> {
+    if a == 0.0 {
+        Vec::new()
+    } else {
+        vec![-b / a]
+    }
+}
+
+// Roots of a*t^2 + b*t + c
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a.abs() <= 1e-12 * (b.abs() + c.abs()) {
+        return solve_linear(b, c);
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return Vec::new();
+    }
+    if discriminant == 0.0 {
+        return vec![-b / (2.0 * a)];
+    }
+    // numerically stable form
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    let mut roots = vec![q / a, c / q];
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+// Roots of a*t^3 + b*t^2 + c*t + d
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if a.abs() <= 1e-12 * (b.abs() + c.abs() + d.abs()) {
+        return solve_quadratic(b, c, d);
+    }
+    // t^3 + a*t^2 + b*t + c, solved with the trigonometric method or Cardano's formula
+    let (a, b, c) = (b / a, c / a, d / a);
+    let q = (a * a - 3.0 * b) / 9.0;
+    let r = (2.0 * a * a * a - 9.0 * a * b + 27.0 * c) / 54.0;
+    let offset = a / 3.0;
+    if r * r < q * q * q {
+        let theta = (r / (q * q * q).sqrt()).clamp(-1.0, 1.0).acos();
+        let scale = -2.0 * q.sqrt();
+        let mut roots: Vec<f64> = [0.0, 2.0, -2.0]
+            .iter()
+            .map(|k| scale * ((theta + k * std::f64::consts::PI) / 3.0).cos() - offset)
+            .collect();
+        roots.sort_by(f64::total_cmp);
+        roots
+    } else {
+        let s = -r.signum() * (r.abs() + (r * r - q * q * q).sqrt()).cbrt();
+        let t = if s == 0.0 { 0.0 } else { q / s };
+        vec![s + t - offset]
+    }
+}
@@ -0,0 +1,154 @@
+// Cubic to quadratic curve conversion, e.g. for TrueType outlines and quadratic-only GPU curve rendering
+
+use cgmath::{EuclideanSpace, InnerSpace, Point2};
+
+use super::{
+    path::{Path, PathCommand},
+    segment::Segment,
+};
+
+const MAX_QUADRATICS_PER_CUBIC: usize = 1024;
+// smaller tolerances (including zero and negative ones) are clamped to this
+const MIN_TOLERANCE: f64 = 1e-9;
+
+impl Segment {
+    // Approximates the segment with quadratic curves within `tolerance`.
+    // Lines and quads are returned unchanged.
+    pub fn to_quadratics(self, tolerance: f64) -> Vec<Segment> {
+        let (p0, p1, p2, p3) = match self {
+            Segment::Cubic(p0, p1, p2, p3) => (p0, p1, p2, p3),
+            _ => return vec![self],
+        };
+
+        // The mid-point quadratic of a cubic deviates from it by at most sqrt(3)/36 * |p3 - 3p2 + 3p1 - p0|
+        // and splitting the cubic into n equal pieces divides that third difference by n^3.
+        let third_difference = (p3 - p0) + (p1 - p2) * 3.0;
+        let error = 3.0f64.sqrt() / 36.0 * third_difference.magnitude();
+        let count = (error / tolerance.max(MIN_TOLERANCE))
+            .cbrt()
+            .ceil()
+            .clamp(1.0, MAX_QUADRATICS_PER_CUBIC as f64) as usize;
+
+        (0..count)
+            .map(|i| {
+                let piece = self.subsegment(i as f64 / count as f64, (i + 1) as f64 / count as f64);
+                match piece {
+                    Segment::Cubic(q0, q1, q2, q3) => {
+                        Segment::Quad(q0, mid_point_control(q0, q1, q2, q3), q3)
+                    }
+                    _ => piece,
+                }
+            })
+            .collect()
+    }
+}
+
+// Control point of the quadratic whose end tangents best match the cubic
+fn mid_point_control(
+    p0: Point2<f64>,
+    p1: Point2<f64>,
+    p2: Point2<f64>,
+    p3: Point2<f64>,
+) -> Point2<f64> {
+    Point2::from_vec(((p1.to_vec() + p2.to_vec()) * 3.0 - (p0.to_vec() + p3.to_vec())) / 4.0)
+}
+
+impl Path {
+    // Replaces every cubic curve with quadratics within `tolerance`
+    pub fn to_quadratics(&self, tolerance: f64) -> Path {
+        let mut path = Path::new();
+        let mut current = Point2::new(0.0, 0.0);
+        for command in self.commands() {
+            match *command {
+                PathCommand::CubicTo(control1, control2, point) => {
+                    for quad in
+                        Segment::Cubic(current, control1, control2, point).to_quadratics(tolerance)
+                    {
+                        path.push_segment(&quad);
+                    }
+                }
+                _ => path.push(*command),
+            }
+            current = path.current_point();
+        }
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::point2;
+
+    use super::*;
+
+    // third difference (-2, 0): the mid-point quadratic is off by sqrt(3)/18
+    fn arch() -> Segment {
+        Segment::Cubic(
+            point2(0.0, 0.0),
+            point2(0.0, 1.0),
+            point2(1.0, 1.0),
+            point2(1.0, 0.0),
+        )
+    }
+
+    // largest distance between the cubic and the quadratics at matching parameters
+    fn max_deviation(cubic: &Segment, quads: &[Segment]) -> f64 {
+        let n = quads.len() as f64;
+        let mut deviation: f64 = 0.0;
+        for (i, quad) in quads.iter().enumerate() {
+            for k in 0..=20 {
+                let s = k as f64 / 20.0;
+                let t = (i as f64 + s) / n;
+                deviation = deviation.max((cubic.point_at(t) - quad.point_at(s)).magnitude());
+            }
+        }
+        deviation
+    }
+
+    #[test]
+    fn piece_count_grows_with_the_cube_root_of_the_error() {
+        for (tolerance, count) in [(1.0, 1), (0.1, 1), (0.01, 3), (0.001, 5), (1e-6, 46)] {
+            let quads = arch().to_quadratics(tolerance);
+            assert_eq!(quads.len(), count, "tolerance {}", tolerance);
+            assert!(quads.iter().all(|q| matches!(q, Segment::Quad(..))));
+            assert!(max_deviation(&arch(), &quads) <= tolerance);
+            // the pieces are connected
+            assert_eq!(quads[0].start(), arch().start());
+            for pair in quads.windows(2) {
+                assert!((pair[0].end() - pair[1].start()).magnitude() < 1e-12);
+            }
+            assert!((quads[count - 1].end() - arch().end()).magnitude() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn non_positive_tolerances_are_clamped() {
+        for tolerance in [0.0, -1.0, f64::NAN] {
+            let quads = arch().to_quadratics(tolerance);
+            assert_eq!(quads.len(), 459);
+            assert!(max_deviation(&arch(), &quads) <= MIN_TOLERANCE);
+            // a degree-elevated quadratic has no error and converts to one piece
+            let quad = Segment::Cubic(
+                point2(0.0, 0.0),
+                point2(2.0, 4.0),
+                point2(4.0, 4.0),
+                point2(6.0, 0.0),
+            );
+            let quads = quad.to_quadratics(tolerance);
+            assert_eq!(quads.len(), 1);
+            assert!(max_deviation(&quad, &quads) < 1e-12);
+        }
+    }
+
+    #[test]
+    fn path_conversion_keeps_other_commands() {
+        let path = Path::from_svg("M0 0 L1 0 C1 1 2 1 2 0 Q3 -1 4 0 Z").unwrap();
+        let converted = path.to_quadratics(0.01);
+        let commands = converted.commands();
+        assert_eq!(commands[..2], path.commands()[..2]);
+        assert!(commands[2..commands.len() - 2]
+            .iter()
+            .all(|c| matches!(c, PathCommand::QuadTo(..))));
+        assert_eq!(commands[commands.len() - 2..], path.commands()[3..]);
+    }
+}
@@ -0,0 +1,101 @@
+use cgmath::{point2, Point2, Vector2};
+
+use super::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rect {
+    pub min: Point2<f64>,
+    pub max: Point2<f64>,
+}
+
+impl Rect {
+    pub fn new(min: Point2<f64>, max: Point2<f64>) -> Rect {
+        Rect { min, max }
+    }
+
+    pub fn from_points(points: impl IntoIterator<Item = Point2<f64>>) -> Option<Rect> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Rect::new(first, first), |rect, point| {
+            rect.union_point(point)
+        }))
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+
+    pub fn size(&self) -> Vector2<f64> {
+        self.max - self.min
+    }
+
+    pub fn center(&self) -> Point2<f64> {
+        self.min + (self.max - self.min) * 0.5
+    }
+
+    pub fn area(&self) -> f64 {
+        self.width() * self.height()
+    }
+
+    // corners in the order (min.x, min.y), (max.x, min.y), (max.x, max.y), (min.x, max.y)
+    pub fn corners(&self) -> [Point2<f64>; 4] {
+        [
+            self.min,
+            point2(self.max.x, self.min.y),
+            self.max,
+            point2(self.min.x, self.max.y),
+        ]
+    }
+
+    pub fn contains(&self, point: Point2<f64>) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.y >= self.min.y
+            && point.y <= self.max.y
+    }
+
+    // Euclidean distance from the point to the rectangle (zero inside)
+    pub fn distance_to_point(&self, point: Point2<f64>) -> f64 {
+        let dx = (self.min.x - point.x).max(point.x - self.max.x).max(0.0);
+        let dy = (self.min.y - point.y).max(point.y - self.max.y).max(0.0);
+        dx.hypot(dy)
+    }
+
+    pub fn contains_rect(&self, other: &Rect) -> bool {
+        self.contains(other.min) && self.contains(other.max)
+    }
+
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect::new(
+            point2(self.min.x.min(other.min.x), self.min.y.min(other.min.y)),
+            point2(self.max.x.max(other.max.x), self.max.y.max(other.max.y)),
+        )
+    }
+
+    pub fn union_point(&self, point: Point2<f64>) -> Rect {
+        self.union(&Rect::new(point, point))
+    }
+
+    pub fn inflate(&self, amount: f64) -> Rect {
+        Rect::new(
+            point2(self.min.x - amount, self.min.y - amount),
+            point2(self.max.x + amount, self.max.y + amount),
+        )
+    }
+
+    pub fn to_path(&self) -> Path {
+        Path::from_polygons(&[self.corners().to_vec()])
+    }
+}
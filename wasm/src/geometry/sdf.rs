@@ -0,0 +1,525 @@
+// Signed distance fields (single and multi-channel) generated on the CPU
+
+use cgmath::{point2, InnerSpace, Point2, Vector2};
+
+use super::{fill_rule::FillRule, path::Path, rect::Rect, segment::Segment};
+
+// Channel masks for MSDF edge colors
+const RED: u8 = 1;
+const GREEN: u8 = 2;
+const BLUE: u8 = 4;
+const WHITE: u8 = RED | GREEN | BLUE;
+const CYAN: u8 = GREEN | BLUE;
+const MAGENTA: u8 = RED | BLUE;
+const YELLOW: u8 = RED | GREEN;
+
+// Turns where the sine of the angle between the edges exceeds this are corners (msdfgen's default of 3 radians)
+const CORNER_CROSS_THRESHOLD: f64 = 0.1411200080598672;
+
+#[derive(Clone, Copy, Debug)]
+pub struct SdfOptions {
+    pub width: usize,
+    pub height: usize,
+    // region of path space covered by the texture; row 0 is at `bounds.min.y`
+    pub bounds: Rect,
+    pub fill_rule: FillRule,
+}
+
+impl SdfOptions {
+    pub fn new(width: usize, height: usize, bounds: Rect) -> SdfOptions {
+        SdfOptions {
+            width,
+            height,
+            bounds,
+            fill_rule: FillRule::NonZero,
+        }
+    }
+
+    fn pixel_center(&self, x: usize, y: usize) -> Point2<f64> {
+        point2(
+            self.bounds.min.x + (x as f64 + 0.5) / self.width as f64 * self.bounds.width(),
+            self.bounds.min.y + (y as f64 + 0.5) / self.height as f64 * self.bounds.height(),
+        )
+    }
+}
+
+// Distances in path units, positive inside the filled region
+#[derive(Clone, Debug, PartialEq)]
+pub struct DistanceField {
+    pub width: usize,
+    pub height: usize,
+    pub channels: usize,
+    // row-major with interleaved channels
+    pub data: Vec<f32>,
+}
+
+impl DistanceField {
+    pub fn get(&self, x: usize, y: usize, channel: usize) -> f32 {
+        self.data[(y * self.width + x) * self.channels + channel]
+    }
+
+    // Maps distances in [-range, range] to [0, 255], with the outline at 127.5
+    pub fn to_u8(&self, range: f64) -> Vec<u8> {
+        self.data
+            .iter()
+            .map(|&distance| {
+                ((0.5 + distance as f64 / (2.0 * range)).clamp(0.0, 1.0) * 255.0).round() as u8
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Edge {
+    segment: Segment,
+    // channels this edge contributes to
+    color: u8,
+    // 1 if the filled side is on the left of the edge, -1 otherwise
+    orientation: f64,
+}
+
+// Exact signed distance to the outline, one channel
+pub fn generate_sdf(path: &Path, options: &SdfOptions) -> DistanceField {
+    let edges = edges(path);
+    let pieces = monotonic_pieces(&edges);
+    generate(options, 1, |point, values| {
+        let distance = edges
+            .iter()
+            .map(|edge| edge.segment.distance_to(point))
+            .fold(f64::INFINITY, f64::min);
+        values[0] = signed(distance, point, &pieces, options.fill_rule);
+    })
+}
+
+// Multi-channel signed distance field that keeps corners sharp when rendered with the median of the channels
+pub fn generate_msdf(path: &Path, options: &SdfOptions) -> DistanceField {
+    let mut edges = edges(path);
+    let pieces = monotonic_pieces(&edges);
+    orient(&mut edges, &pieces, options.fill_rule);
+    generate(options, 3, |point, values| {
+        let mut nearest: [Option<(f64, f64, &Edge)>; 3] = [None; 3];
+        for edge in &edges {
+            let t = edge.segment.closest_parameter(point);
+            let offset = point - edge.segment.point_at(t);
+            let distance = offset.magnitude();
+            let direction = direction_at(&edge.segment, t);
+            // prefer edges met head-on when several are equally close (e.g. at corners)
+            let obliqueness = if distance > 0.0 {
+                direction.dot(offset / distance).abs()
+            } else {
+                0.0
+            };
+            for (channel, nearest) in nearest.iter_mut().enumerate() {
+                if edge.color & (1 << channel) == 0 {
+                    continue;
+                }
+                let closer = match nearest {
+                    None => true,
+                    Some((best, best_obliqueness, _)) => {
+                        let tie = 1e-9 * (1.0 + *best);
+                        distance < *best - tie
+                            || (distance <= *best + tie && obliqueness < *best_obliqueness)
+                    }
+                };
+                if closer {
+                    *nearest = Some((distance, obliqueness, edge));
+                }
+            }
+        }
+
+        for (channel, nearest) in nearest.iter().enumerate() {
+            values[channel] = match nearest {
+                Some((_, _, edge)) => pseudo_distance(edge, point),
+                None => f32::NEG_INFINITY,
+            };
+        }
+
+        // channels disagreeing with the true sign (e.g. where contours overlap) fall back to the plain distance
+        let true_distance = nearest
+            .iter()
+            .flatten()
+            .map(|(distance, _, _)| *distance)
+            .fold(f64::INFINITY, f64::min);
+        let true_distance = signed(true_distance, point, &pieces, options.fill_rule);
+        if (median(values[0], values[1], values[2]) > 0.0) != (true_distance > 0.0) {
+            values.fill(true_distance);
+        }
+    })
+}
+
+fn generate(
+    options: &SdfOptions,
+    channels: usize,
+    mut pixel: impl FnMut(Point2<f64>, &mut [f32]),
+) -> DistanceField {
+    let mut data = vec![0.0; options.width * options.height * channels];
+    for y in 0..options.height {
+        for x in 0..options.width {
+            let index = (y * options.width + x) * channels;
+            pixel(
+                options.pixel_center(x, y),
+                &mut data[index..index + channels],
+            );
+        }
+    }
+    DistanceField {
+        width: options.width,
+        height: options.height,
+        channels,
+        data,
+    }
+}
+
+fn signed(distance: f64, point: Point2<f64>, pieces: &[Segment], fill_rule: FillRule) -> f32 {
+    if fill_rule.is_inside(winding_number(pieces, point)) {
+        distance as f32
+    } else {
+        -distance as f32
+    }
+}
+
+fn median(a: f32, b: f32, c: f32) -> f32 {
+    a.min(b).max(a.max(b).min(c))
+}
+
+// Closed contours of the path with MSDF colors assigned to their edges
+fn edges(path: &Path) -> Vec<Edge> {
+    let mut edges = Vec::new();
+    for subpath in path.subpaths() {
+        let mut segments: Vec<Segment> = subpath
+            .segments
+            .iter()
+            .filter(|segment| segment.start_tangent().magnitude2() > 0.0)
+            .copied()
+            .collect();
+        if subpath.end() != subpath.start() {
+            segments.push(Segment::Line(subpath.end(), subpath.start()));
+        }
+        if segments.is_empty() {
+            continue;
+        }
+
+        let colors = edge_colors(&segments);
+        edges.extend(
+            segments
+                .into_iter()
+                .zip(colors)
+                .map(|(segment, color)| Edge {
+                    segment,
+                    color,
+                    // set by `orient`
+                    orientation: 1.0,
+                }),
+        );
+    }
+    edges
+}
+
+// Finds the filled side of each edge from the fill itself, just left of the edge's midpoint.
+// The winding of the edge's own contour can't tell: a hole is wound against its outer contour.
+fn orient(edges: &mut [Edge], pieces: &[Segment], fill_rule: FillRule) {
+    for edge in edges {
+        let middle = edge.segment.point_at(0.5);
+        let direction = direction_at(&edge.segment, 0.5);
+        let step = 1e-6 * (1.0 + middle.x.abs() + middle.y.abs());
+        let left = middle + Vector2::new(-direction.y, direction.x) * step;
+        edge.orientation = if fill_rule.is_inside(winding_number(pieces, left)) {
+            1.0
+        } else {
+            -1.0
+        };
+    }
+}
+
+// Colors edges so that the two edges meeting at a corner share exactly one channel
+fn edge_colors(segments: &[Segment]) -> Vec<u8> {
+    let n = segments.len();
+    let corners: Vec<usize> = (0..n)
+        .filter(|&i| {
+            let incoming = segments[(i + n - 1) % n].end_tangent().normalize();
+            let outgoing = segments[i].start_tangent().normalize();
+            incoming.dot(outgoing) <= 0.0
+                || cross(incoming, outgoing).abs() > CORNER_CROSS_THRESHOLD
+        })
+        .collect();
+
+    let mut colors = vec![WHITE; n];
+    match corners.len() {
+        0 => {}
+        1 => {
+            // a single corner ("teardrop") is kept by splitting the contour into three differently colored runs
+            let corner = corners[0];
+            if n >= 2 {
+                for k in 0..n {
+                    colors[(corner + k) % n] = if n == 2 {
+                        [MAGENTA, YELLOW][k]
+                    } else {
+                        [MAGENTA, WHITE, YELLOW][3 * k / n]
+                    };
+                }
+            }
+        }
+        count => {
+            let cycle = [CYAN, MAGENTA, YELLOW];
+            let mut group_colors: Vec<u8> = (0..count).map(|g| cycle[g % 3]).collect();
+            // the last run also meets the first one
+            if group_colors[count - 1] == group_colors[0] {
+                group_colors[count - 1] = *cycle
+                    .iter()
+                    .find(|&&color| color != group_colors[0] && color != group_colors[count - 2])
+                    .unwrap();
+            }
+            for (g, &corner) in corners.iter().enumerate() {
+                let next = corners[(g + 1) % count];
+                let mut i = corner;
+                loop {
+                    colors[i] = group_colors[g];
+                    i = (i + 1) % n;
+                    if i == next {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    colors
+}
+
+// Signed distance to the edge, extended along its end tangents beyond its endpoints
+fn pseudo_distance(edge: &Edge, point: Point2<f64>) -> f32 {
+    let segment = &edge.segment;
+    let t = segment.closest_parameter(point);
+    if t <= 0.0 {
+        let direction = segment.start_tangent().normalize();
+        let offset = point - segment.start();
+        if offset.dot(direction) < 0.0 {
+            return (cross(direction, offset) * edge.orientation) as f32;
+        }
+    } else if t >= 1.0 {
+        let direction = segment.end_tangent().normalize();
+        let offset = point - segment.end();
+        if offset.dot(direction) > 0.0 {
+            return (cross(direction, offset) * edge.orientation) as f32;
+        }
+    }
+    let offset = point - segment.point_at(t);
+    let side = cross(direction_at(segment, t), offset) * edge.orientation;
+    let distance = offset.magnitude();
+    (if side < 0.0 { -distance } else { distance }) as f32
+}
+
+fn direction_at(segment: &Segment, t: f64) -> Vector2<f64> {
+    let derivative = segment.derivative_at(t);
+    if derivative.magnitude2() > 0.0 {
+        derivative.normalize()
+    } else if t < 0.5 {
+        segment.start_tangent().normalize()
+    } else {
+        segment.end_tangent().normalize()
+    }
+}
+
+fn cross(a: Vector2<f64>, b: Vector2<f64>) -> f64 {
+    a.x * b.y - a.y * b.x
+}
+
+// Edges split into pieces monotonic in y, for exact winding numbers with curves
+fn monotonic_pieces(edges: &[Edge]) -> Vec<Segment> {
+    edges
+        .iter()
+        .flat_map(|edge| edge.segment.split_monotonic())
+        .collect()
+}
+
+// Same convention as `polygons_winding_number`, with a ray cast towards +x
+fn winding_number(pieces: &[Segment], point: Point2<f64>) -> i32 {
+    let mut winding = 0;
+    for piece in pieces {
+        let (start, end) = (piece.start(), piece.end());
+        let direction = if start.y <= point.y && end.y > point.y {
+            1
+        } else if end.y <= point.y && start.y > point.y {
+            -1
+        } else {
+            continue;
+        };
+
+        // control points bound the curve, so most pieces are decided without solving for the crossing
+        let points = piece.points();
+        let min_x = points.iter().map(|p| p.x).fold(f64::INFINITY, f64::min);
+        let max_x = points.iter().map(|p| p.x).fold(f64::NEG_INFINITY, f64::max);
+        let crosses = if min_x > point.x {
+            true
+        } else if max_x <= point.x {
+            false
+        } else {
+            // bisection for the crossing height on the monotonic piece
+            let (mut low, mut high) = (0.0, 1.0);
+            for _ in 0..50 {
+                let middle = (low + high) / 2.0;
+                if (piece.point_at(middle).y > point.y) == (direction > 0) {
+                    high = middle;
+                } else {
+                    low = middle;
+                }
+            }
+            piece.point_at((low + high) / 2.0).x > point.x
+        };
+        if crosses {
+            winding += direction;
+        }
+    }
+    winding
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::MetricSpace;
+
+    use super::*;
+    use crate::geometry::circle::Circle;
+
+    fn square() -> Path {
+        Path::from_svg("M0 0 H10 V10 H0 Z").unwrap()
+    }
+
+    // one pixel per path unit over [-5, 15]^2, so pixel (x, y) is centered at (x - 4.5, y - 4.5)
+    fn options() -> SdfOptions {
+        SdfOptions::new(20, 20, Rect::new(point2(-5.0, -5.0), point2(15.0, 15.0)))
+    }
+
+    fn msdf_median(field: &DistanceField, x: usize, y: usize) -> f32 {
+        median(field.get(x, y, 0), field.get(x, y, 1), field.get(x, y, 2))
+    }
+
+    #[test]
+    fn square_sdf_sign_and_distance() {
+        let field = generate_sdf(&square(), &options());
+        assert_eq!((field.width, field.height, field.channels), (20, 20, 1));
+        for y in 0..20 {
+            for x in 0..20 {
+                let point = options().pixel_center(x, y);
+                // distance to the nearest side inside, to the square outside
+                let inside = (0.0..=10.0).contains(&point.x) && (0.0..=10.0).contains(&point.y);
+                let expected = if inside {
+                    point.x.min(point.y).min(10.0 - point.x).min(10.0 - point.y)
+                } else {
+                    let nearest = point2(point.x.clamp(0.0, 10.0), point.y.clamp(0.0, 10.0));
+                    -point.distance(nearest)
+                };
+                assert!(
+                    (field.get(x, y, 0) as f64 - expected).abs() < 1e-5,
+                    "({}, {}): {} != {}",
+                    x,
+                    y,
+                    field.get(x, y, 0),
+                    expected
+                );
+            }
+        }
+        assert_eq!(field.get(10, 10, 0), 4.5);
+        assert_eq!(field.get(2, 10, 0), -2.5);
+    }
+
+    #[test]
+    fn circle_sdf_sign_and_distance() {
+        let center = point2(5.0, 5.0);
+        let field = generate_sdf(&Circle::new(center, 8.0).to_path(), &options());
+        for y in 0..20 {
+            for x in 0..20 {
+                let expected = 8.0 - options().pixel_center(x, y).distance(center);
+                // the cubic arcs are within 0.03% of the radius of the true circle
+                assert!((field.get(x, y, 0) as f64 - expected).abs() < 8.0 * 3e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn fill_rule_decides_the_sign() {
+        // a hole with the same orientation as the outer contour
+        let path = Path::from_svg("M0 0 H10 V10 H0 Z M3 3 H7 V7 H3 Z").unwrap();
+        let mut options = options();
+        let center = (9, 9);
+        assert!(generate_sdf(&path, &options).get(center.0, center.1, 0) > 0.0);
+        options.fill_rule = FillRule::EvenOdd;
+        assert!(generate_sdf(&path, &options).get(center.0, center.1, 0) < 0.0);
+    }
+
+    #[test]
+    fn msdf_median_keeps_square_corners_sharp() {
+        let sdf = generate_sdf(&square(), &options());
+        let msdf = generate_msdf(&square(), &options());
+        assert_eq!(msdf.channels, 3);
+        for y in 0..20 {
+            for x in 0..20 {
+                let point = options().pixel_center(x, y);
+                let median = msdf_median(&msdf, x, y);
+                assert_eq!(median > 0.0, sdf.get(x, y, 0) > 0.0, "({}, {})", x, y);
+                // the median is the signed distance of a square with sharp corners at every level
+                let (dx, dy) = (
+                    (-point.x).max(point.x - 10.0),
+                    (-point.y).max(point.y - 10.0),
+                );
+                let expected = -dx.max(dy);
+                assert!(
+                    (median as f64 - expected).abs() < 1e-5,
+                    "({}, {}): {} != {}",
+                    x,
+                    y,
+                    median,
+                    expected
+                );
+            }
+        }
+        // outside a corner the true distance is rounded, the median isn't
+        assert!((sdf.get(0, 0, 0) - -(4.5f32 * 2f32.sqrt())).abs() < 1e-5);
+        assert!((msdf_median(&msdf, 0, 0) - -4.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn msdf_median_keeps_hole_corners_sharp() {
+        // the hole is wound against the outer contour
+        let path = Path::from_svg("M0 0 H10 V10 H0 Z M3 3 V7 H7 V3 Z").unwrap();
+        let msdf = generate_msdf(&path, &options());
+        let mut checked = 0;
+        for y in 0..20 {
+            for x in 0..20 {
+                let point = options().pixel_center(x, y);
+                // signed distance to the hole with sharp corners, positive in the fill around it
+                let (dx, dy) = (
+                    (3.0 - point.x).max(point.x - 7.0),
+                    (3.0 - point.y).max(point.y - 7.0),
+                );
+                let expected = dx.max(dy);
+                let outer = point.x.min(point.y).min(10.0 - point.x).min(10.0 - point.y);
+                if expected >= outer {
+                    continue;
+                }
+                let median = msdf_median(&msdf, x, y);
+                assert!(
+                    (median as f64 - expected).abs() < 1e-5,
+                    "({}, {}): {} != {}",
+                    x,
+                    y,
+                    median,
+                    expected
+                );
+                checked += 1;
+            }
+        }
+        assert!(checked > 30, "{}", checked);
+        // diagonally off a hole corner the true distance is rounded, the median isn't
+        assert!((msdf_median(&msdf, 7, 7) - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn to_u8_maps_the_outline_to_the_middle() {
+        let field = DistanceField {
+            width: 4,
+            height: 1,
+            channels: 1,
+            data: vec![-3.0, -1.0, 0.0, 1.0],
+        };
+        assert_eq!(field.to_u8(2.0), vec![0, 64, 128, 191]);
+    }
+}
@@ -1,7 +1,220 @@
-use cgmath::Point2;
+use cgmath::{EuclideanSpace, InnerSpace, Point2, Vector2};
 
+use super::{
+    polynomial::solve_quadratic,
+    rect::Rect,
+    subdivision::{subdivideCubic, subdivideQuad},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Segment {
     Line(Point2<f64>, Point2<f64>),
     Quad(Point2<f64>, Point2<f64>, Point2<f64>),
     Cubic(Point2<f64>, Point2<f64>, Point2<f64>, Point2<f64>),
 }
+
+impl Segment {
+    pub fn start(&self) -> Point2<f64> {
+        match *self {
+            Segment::Line(p0, _) | Segment::Quad(p0, _, _) | Segment::Cubic(p0, _, _, _) => p0,
+        }
+    }
+
+    pub fn end(&self) -> Point2<f64> {
+        match *self {
+            Segment::Line(_, p1) | Segment::Quad(_, _, p1) | Segment::Cubic(_, _, _, p1) => p1,
+        }
+    }
+
+    pub fn point_at(&self, t: f64) -> Point2<f64> {
+        let mt = 1.0 - t;
+        match *self {
+            Segment::Line(p0, p1) => p0 + (p1 - p0) * t,
+            Segment::Quad(p0, p1, p2) => Point2::from_vec(
+                p0.to_vec() * (mt * mt) + p1.to_vec() * (2.0 * mt * t) + p2.to_vec() * (t * t),
+            ),
+            Segment::Cubic(p0, p1, p2, p3) => Point2::from_vec(
+                p0.to_vec() * (mt * mt * mt)
+                    + p1.to_vec() * (3.0 * mt * mt * t)
+                    + p2.to_vec() * (3.0 * mt * t * t)
+                    + p3.to_vec() * (t * t * t),
+            ),
+        }
+    }
+
+    pub fn derivative_at(&self, t: f64) -> Vector2<f64> {
+        let mt = 1.0 - t;
+        match *self {
+            Segment::Line(p0, p1) => p1 - p0,
+            Segment::Quad(p0, p1, p2) => (p1 - p0) * (2.0 * mt) + (p2 - p1) * (2.0 * t),
+            Segment::Cubic(p0, p1, p2, p3) => {
+                (p1 - p0) * (3.0 * mt * mt) + (p2 - p1) * (6.0 * mt * t) + (p3 - p2) * (3.0 * t * t)
+            }
+        }
+    }
+
+    // Direction at the start, skipping control points that coincide with the start point
+    pub fn start_tangent(&self) -> Vector2<f64> {
+        let start = self.start();
+        self.points()
+            .iter()
+            .skip(1)
+            .map(|point| point - start)
+            .find(|direction| direction.x != 0.0 || direction.y != 0.0)
+            .unwrap_or(Vector2::new(0.0, 0.0))
+    }
+
+    // Direction at the end, skipping control points that coincide with the end point
+    pub fn end_tangent(&self) -> Vector2<f64> {
+        let end = self.end();
+        self.points()
+            .iter()
+            .rev()
+            .skip(1)
+            .map(|point| end - point)
+            .find(|direction| direction.x != 0.0 || direction.y != 0.0)
+            .unwrap_or(Vector2::new(0.0, 0.0))
+    }
+
+    pub fn points(&self) -> Vec<Point2<f64>> {
+        match *self {
+            Segment::Line(p0, p1) => vec![p0, p1],
+            Segment::Quad(p0, p1, p2) => vec![p0, p1, p2],
+            Segment::Cubic(p0, p1, p2, p3) => vec![p0, p1, p2, p3],
+        }
+    }
+
+    // de Casteljau subdivision at t
+    pub fn split(&self, t: f64) -> (Segment, Segment) {
+        let lerp = |a: Point2<f64>, b: Point2<f64>| a + (b - a) * t;
+        match *self {
+            Segment::Line(p0, p1) => {
+                let p = lerp(p0, p1);
+                (Segment::Line(p0, p), Segment::Line(p, p1))
+            }
+            Segment::Quad(p0, p1, p2) => {
+                let (p01, p12) = (lerp(p0, p1), lerp(p1, p2));
+                let p = lerp(p01, p12);
+                (Segment::Quad(p0, p01, p), Segment::Quad(p, p12, p2))
+            }
+            Segment::Cubic(p0, p1, p2, p3) => {
+                let (p01, p12, p23) = (lerp(p0, p1), lerp(p1, p2), lerp(p2, p3));
+                let (p012, p123) = (lerp(p01, p12), lerp(p12, p23));
+                let p = lerp(p012, p123);
+                (
+                    Segment::Cubic(p0, p01, p012, p),
+                    Segment::Cubic(p, p123, p23, p3),
+                )
+            }
+        }
+    }
+
+    // The part of the curve between parameters t0 and t1
+    pub fn subsegment(&self, t0: f64, t1: f64) -> Segment {
+        if t1 <= 0.0 {
+            return self.split(0.0).0;
+        }
+        let (head, _) = self.split(t1);
+        head.split(t0 / t1).1
+    }
+
+    // Arc length between t0 and t1 (Gauss-Legendre quadrature)
+    pub fn arc_length(&self, t0: f64, t1: f64) -> f64 {
+        const NODES: [(f64, f64); 5] = [
+            (0.0, 0.5688888888888889),
+            (-0.5384693101056831, 0.4786286704993665),
+            (0.5384693101056831, 0.4786286704993665),
+            (-0.906179845938664, 0.2369268850561891),
+            (0.906179845938664, 0.2369268850561891),
+        ];
+        const PIECES: usize = 4;
+        let step = (t1 - t0) / PIECES as f64;
+        (0..PIECES)
+            .map(|piece| {
+                let a = t0 + step * piece as f64;
+                NODES
+                    .iter()
+                    .map(|(x, w)| w * self.derivative_at(a + step * (x + 1.0) / 2.0).magnitude())
+                    .sum::<f64>()
+                    * step
+                    / 2.0
+            })
+            .sum()
+    }
+
+    pub fn length(&self) -> f64 {
+        match *self {
+            Segment::Line(p0, p1) => (p1 - p0).magnitude(),
+            _ => self.arc_length(0.0, 1.0),
+        }
+    }
+
+    // Parameter at which the arc length from the start reaches `length`
+    pub fn parameter_at_length(&self, length: f64) -> f64 {
+        let total = self.length();
+        if total <= 0.0 || length <= 0.0 {
+            return 0.0;
+        }
+        if length >= total {
+            return 1.0;
+        }
+        if let Segment::Line(..) = self {
+            return length / total;
+        }
+        let (mut low, mut high) = (0.0, 1.0);
+        for _ in 0..40 {
+            let middle = (low + high) / 2.0;
+            if self.arc_length(0.0, middle) < length {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        (low + high) / 2.0
+    }
+
+    // Parameters in (0, 1) where the derivative of x (axis 0) or y (axis 1) is zero
+    pub fn extrema(&self, axis: usize) -> Vec<f64> {
+        let roots = match *self {
+            Segment::Line(..) => Vec::new(),
+            Segment::Quad(p0, p1, p2) => {
+                let (a, b) = (p1[axis] - p0[axis], p2[axis] - p1[axis]);
+                if a == b {
+                    Vec::new()
+                } else {
+                    vec![a / (a - b)]
+                }
+            }
+            Segment::Cubic(p0, p1, p2, p3) => {
+                let (a, b, c) = (
+                    p1[axis] - p0[axis],
+                    p2[axis] - p1[axis],
+                    p3[axis] - p2[axis],
+                );
+                solve_quadratic(a - 2.0 * b + c, 2.0 * (b - a), a)
+            }
+        };
+        roots.into_iter().filter(|&t| t > 0.0 && t < 1.0).collect()
+    }
+
+    // Tight bounding box of the curve
+    pub fn bounds(&self) -> Rect {
+        let mut rect = Rect::new(self.start(), self.start()).union_point(self.end());
+        for axis in 0..2 {
+            for t in self.extrema(axis) {
+                rect = rect.union_point(self.point_at(t));
+            }
+        }
+        rect
+    }
+
+    // Polyline approximation, excluding the start point
+    pub fn flatten(&self) -> Vec<Point2<f64>> {
+        match *self {
+            Segment::Line(_, p1) => vec![p1],
+            Segment::Quad(p0, p1, p2) => subdivideQuad(p0, p1, p2),
+            Segment::Cubic(p0, p1, p2, p3) => subdivideCubic(p0, p1, p2, p3),
+        }
+    }
+}
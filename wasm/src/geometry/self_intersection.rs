@@ -0,0 +1,457 @@
+use std::{cmp::Ordering, collections::HashMap, f64::consts::PI};
+
+use cgmath::{vec2, InnerSpace, Point2, Vector2};
+
+use super::{
+    fill_rule::FillRule,
+    path::{edge_winding, Path},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SelfIntersection {
+    pub point: Point2<f64>,
+    // indices of the crossing segments in the subpath
+    // (the implicit closing line of the subpath has index `segments.len()`)
+    pub segments: (usize, usize),
+}
+
+impl Path {
+    // Crossing points of each subpath with itself, computed on the flattened contours
+    pub fn self_intersections(&self) -> Vec<Vec<SelfIntersection>> {
+        self.subpaths()
+            .iter()
+            .map(|subpath| {
+                let mut edges = Vec::new();
+                let mut sources = Vec::new();
+                let mut start = subpath.start();
+                for (index, segment) in subpath.segments.iter().enumerate() {
+                    for point in segment.flatten() {
+                        if point != start {
+                            edges.push((start, point));
+                            sources.push(index);
+                        }
+                        start = point;
+                    }
+                }
+                if start != subpath.start() {
+                    edges.push((start, subpath.start()));
+                    sources.push(subpath.segments.len());
+                }
+
+                let n = edges.len();
+                let mut intersections = Vec::new();
+                for_each_overlapping_pair(&edges, |i, j| {
+                    // consecutive edges always meet at their shared vertex
+                    if j == i + 1 || (i == 0 && j == n - 1) {
+                        return;
+                    }
+                    let (p0, p1) = edges[i];
+                    let (q0, q1) = edges[j];
+                    if let Some((t, u)) = intersect_lines(p0, p1, q0, q1) {
+                        // half-open ranges so that crossings at vertices are reported once
+                        if (0.0..1.0).contains(&t) && (0.0..1.0).contains(&u) {
+                            intersections.push(SelfIntersection {
+                                point: p0 + (p1 - p0) * t,
+                                segments: (sources[i], sources[j]),
+                            });
+                        }
+                    }
+                });
+                intersections
+            })
+            .collect()
+    }
+
+    // Splits the path into simple, non-overlapping loops covering the same filled region.
+    // Outer loops are counter-clockwise and holes clockwise (in y-up coordinates).
+    // Curves are flattened in the result.
+    pub fn resolve_self_intersections(&self, fill_rule: FillRule) -> Path {
+        Path::from_polygons(&resolve_polygons(&self.to_polygons(), |winding| {
+            fill_rule.is_inside(winding)
+        }))
+    }
+}
+
+fn cross(a: Vector2<f64>, b: Vector2<f64>) -> f64 {
+    a.x * b.y - a.y * b.x
+}
+
+// Parameters (t, u) of the intersection of lines p0-p1 and q0-q1, or None if they are parallel
+fn intersect_lines(
+    p0: Point2<f64>,
+    p1: Point2<f64>,
+    q0: Point2<f64>,
+    q1: Point2<f64>,
+) -> Option<(f64, f64)> {
+    let r = p1 - p0;
+    let s = q1 - q0;
+    let denominator = cross(r, s);
+    if denominator.abs() <= f64::EPSILON * r.magnitude() * s.magnitude() {
+        return None;
+    }
+    let qp = q0 - p0;
+    Some((cross(qp, s) / denominator, cross(qp, r) / denominator))
+}
+
+// Calls `f(i, j)` (i < j) for each pair of edges whose bounding boxes overlap
+fn for_each_overlapping_pair(
+    edges: &[(Point2<f64>, Point2<f64>)],
+    mut f: impl FnMut(usize, usize),
+) {
+    let min_x = |i: usize| edges[i].0.x.min(edges[i].1.x);
+    let mut order: Vec<usize> = (0..edges.len()).collect();
+    order.sort_by(|&i, &j| min_x(i).total_cmp(&min_x(j)));
+
+    for (k, &i) in order.iter().enumerate() {
+        let (a, b) = edges[i];
+        let max_x = a.x.max(b.x);
+        let (min_y, max_y) = (a.y.min(b.y), a.y.max(b.y));
+        for &j in &order[k + 1..] {
+            if min_x(j) > max_x {
+                break;
+            }
+            let (c, d) = edges[j];
+            if c.y.max(d.y) >= min_y && c.y.min(d.y) <= max_y {
+                f(i.min(j), i.max(j));
+            }
+        }
+    }
+}
+
+// Resolves closed polygons into simple loops enclosing the region where `is_inside(winding)` holds.
+// The loops have the region on their left side.
+pub fn resolve_polygons(
+    polygons: &[Vec<Point2<f64>>],
+    is_inside: impl Fn(i32) -> bool,
+) -> Vec<Vec<Point2<f64>>> {
+    let edges: Vec<(Point2<f64>, Point2<f64>)> = polygons
+        .iter()
+        .flat_map(|polygon| {
+            (0..polygon.len()).map(move |i| (polygon[i], polygon[(i + 1) % polygon.len()]))
+        })
+        .filter(|(a, b)| a != b)
+        .collect();
+
+    let mut min = vec2(f64::INFINITY, f64::INFINITY);
+    let mut max = vec2(f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for (a, _) in &edges {
+        min = vec2(min.x.min(a.x), min.y.min(a.y));
+        max = vec2(max.x.max(a.x), max.y.max(a.y));
+    }
+    let extent = (max.x - min.x).max(max.y - min.y);
+    if edges.is_empty() || extent.partial_cmp(&0.0) != Some(Ordering::Greater) {
+        return Vec::new();
+    }
+    // points closer than this are merged into one vertex
+    let snap = extent * 1e-9;
+
+    // split every edge at its intersections with the other edges
+    // (intersection points are computed once and shared by both edges so that they snap identically)
+    let mut splits: Vec<Vec<(f64, Point2<f64>)>> = edges
+        .iter()
+        .map(|&(a, b)| vec![(0.0, a), (1.0, b)])
+        .collect();
+    for_each_overlapping_pair(&edges, |i, j| {
+        let (p0, p1) = edges[i];
+        let (q0, q1) = edges[j];
+        match intersect_lines(p0, p1, q0, q1) {
+            Some((t, u)) => {
+                let range = -1e-12..=1.0 + 1e-12;
+                if range.contains(&t) && range.contains(&u) {
+                    let point = p0 + (p1 - p0) * t.clamp(0.0, 1.0);
+                    splits[i].push((t, point));
+                    splits[j].push((u, point));
+                }
+            }
+            None => {
+                // parallel edges: split at each other's endpoints where they overlap
+                add_collinear_splits(&mut splits[i], p0, p1, &[q0, q1], snap);
+                add_collinear_splits(&mut splits[j], q0, q1, &[p0, p1], snap);
+            }
+        }
+    });
+
+    let mut vertices: Vec<Point2<f64>> = Vec::new();
+    let mut vertex_ids: HashMap<(i64, i64), usize> = HashMap::new();
+    // sub-edges (u, v) with u < v, and how many more of the original edges run along them
+    // from u to v than from v to u
+    let mut sub_edges: Vec<(usize, usize)> = Vec::new();
+    let mut multiplicities: Vec<i32> = Vec::new();
+    let mut sub_edge_ids: HashMap<(usize, usize), usize> = HashMap::new();
+    for split in &mut splits {
+        split.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let ids: Vec<usize> = split
+            .iter()
+            .map(|(_, point)| {
+                let key = (
+                    (point.x / snap).round() as i64,
+                    (point.y / snap).round() as i64,
+                );
+                *vertex_ids.entry(key).or_insert_with(|| {
+                    vertices.push(*point);
+                    vertices.len() - 1
+                })
+            })
+            .collect();
+        for pair in ids.windows(2) {
+            if pair[0] == pair[1] {
+                continue;
+            }
+            let key = (pair[0].min(pair[1]), pair[0].max(pair[1]));
+            let id = *sub_edge_ids.entry(key).or_insert_with(|| {
+                sub_edges.push(key);
+                multiplicities.push(0);
+                sub_edges.len() - 1
+            });
+            multiplicities[id] += if pair[0] < pair[1] { 1 } else { -1 };
+        }
+    }
+
+    // Keep the edges separating inside from outside, directed with the inside on the left.
+    // The winding number right of each sub-edge comes from one sweep over the original edges;
+    // crossing the original edges along the sub-edge from right to left adds their multiplicity.
+    let samples: Vec<Point2<f64>> = sub_edges
+        .iter()
+        .map(|&(u, v)| {
+            let (a, b) = (vertices[u], vertices[v]);
+            let direction = b - a;
+            let length = direction.magnitude();
+            let normal = vec2(-direction.y, direction.x) / length;
+            a + direction * 0.5 - normal * (length * 0.01).min(extent * 1e-7)
+        })
+        .collect();
+    let right_windings = winding_numbers(&edges, &samples);
+    let mut boundary = Vec::new();
+    for ((&(u, v), multiplicity), right_winding) in
+        sub_edges.iter().zip(multiplicities).zip(right_windings)
+    {
+        let left = is_inside(right_winding + multiplicity);
+        let right = is_inside(right_winding);
+        match (left, right) {
+            (true, false) => boundary.push((u, v)),
+            (false, true) => boundary.push((v, u)),
+            _ => {}
+        }
+    }
+
+    let mut outgoing = vec![Vec::new(); vertices.len()];
+    for (index, &(from, _)) in boundary.iter().enumerate() {
+        outgoing[from].push(index);
+    }
+    // At each vertex, continue with the first outgoing edge clockwise from the incoming edge.
+    // This keeps loops that touch at a vertex separate.
+    let next = |edge: usize| -> Option<usize> {
+        let (from, to) = boundary[edge];
+        let back = vertices[from] - vertices[to];
+        let back_angle = back.y.atan2(back.x);
+        outgoing[to]
+            .iter()
+            .map(|&candidate| {
+                let direction = vertices[boundary[candidate].1] - vertices[to];
+                let mut angle = (back_angle - direction.y.atan2(direction.x)).rem_euclid(2.0 * PI);
+                if angle == 0.0 {
+                    angle = 2.0 * PI;
+                }
+                (candidate, angle)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(candidate, _)| candidate)
+    };
+
+    let mut used = vec![false; boundary.len()];
+    let mut loops = Vec::new();
+    for start in 0..boundary.len() {
+        let mut polygon = Vec::new();
+        let mut edge = Some(start);
+        while let Some(index) = edge.filter(|&index| !used[index]) {
+            used[index] = true;
+            polygon.push(vertices[boundary[index].0]);
+            edge = next(index);
+        }
+        remove_collinear_points(&mut polygon);
+        if polygon.len() >= 3 {
+            loops.push(polygon);
+        }
+    }
+    loops
+}
+
+// Winding numbers of the closed polygons formed by the edges around each point.
+// A horizontal line sweeps down the points, so each point is only tested against the edges it crosses.
+fn winding_numbers(edges: &[(Point2<f64>, Point2<f64>)], points: &[Point2<f64>]) -> Vec<i32> {
+    let top = |i: usize| edges[i].0.y.min(edges[i].1.y);
+    let bottom = |i: usize| edges[i].0.y.max(edges[i].1.y);
+    let mut by_top: Vec<usize> = (0..edges.len()).collect();
+    by_top.sort_by(|&i, &j| top(i).total_cmp(&top(j)));
+    let mut by_y: Vec<usize> = (0..points.len()).collect();
+    by_y.sort_by(|&i, &j| points[i].y.total_cmp(&points[j].y));
+
+    let mut windings = vec![0; points.len()];
+    let mut active: Vec<usize> = Vec::new();
+    let mut next_edge = 0;
+    for index in by_y {
+        let point = points[index];
+        while next_edge < by_top.len() && top(by_top[next_edge]) <= point.y {
+            active.push(by_top[next_edge]);
+            next_edge += 1;
+        }
+        // like `edge_winding`, an edge spans the rows from its top up to but excluding its bottom
+        active.retain(|&i| bottom(i) > point.y);
+        windings[index] = active
+            .iter()
+            .map(|&i| edge_winding(edges[i].0, edges[i].1, point))
+            .sum();
+    }
+    windings
+}
+
+fn add_collinear_splits(
+    split: &mut Vec<(f64, Point2<f64>)>,
+    p0: Point2<f64>,
+    p1: Point2<f64>,
+    points: &[Point2<f64>],
+    snap: f64,
+) {
+    let r = p1 - p0;
+    let length = r.magnitude();
+    for &point in points {
+        let distance = cross(r, point - p0).abs() / length;
+        let t = (point - p0).dot(r) / (length * length);
+        if distance <= snap && t > 0.0 && t < 1.0 {
+            split.push((t, point));
+        }
+    }
+}
+
+fn remove_collinear_points(polygon: &mut Vec<Point2<f64>>) {
+    let mut i = 0;
+    while polygon.len() >= 3 && i < polygon.len() {
+        let n = polygon.len();
+        let previous = polygon[(i + n - 1) % n];
+        let next = polygon[(i + 1) % n];
+        let a = polygon[i] - previous;
+        let b = next - polygon[i];
+        if cross(a, b).abs() <= 1e-12 * a.magnitude() * b.magnitude() && a.dot(b) > 0.0 {
+            polygon.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{point2, EuclideanSpace};
+
+    use super::*;
+
+    // crosses itself at (1, 1)
+    fn figure_eight() -> Path {
+        Path::from_svg("M0 0 L2 2 L2 0 L0 2 Z").unwrap()
+    }
+
+    // pentagram drawn in one stroke, so its center is wound twice
+    fn star() -> Path {
+        let points: Vec<Point2<f64>> = (0..5)
+            .map(|i| {
+                let angle = PI / 2.0 + i as f64 * 4.0 * PI / 5.0;
+                point2(10.0 * angle.cos(), 10.0 * angle.sin())
+            })
+            .collect();
+        Path::from_polygons(&[points])
+    }
+
+    fn signed_area(polygon: &[Point2<f64>]) -> f64 {
+        let n = polygon.len();
+        (0..n)
+            .map(|i| cross(polygon[i].to_vec(), polygon[(i + 1) % n].to_vec()))
+            .sum::<f64>()
+            / 2.0
+    }
+
+    // The resolved path covers the same points with either fill rule
+    fn assert_same_region(original: &Path, resolved: &Path, fill_rule: FillRule) {
+        for i in 0..40 {
+            for j in 0..40 {
+                let point = point2(-10.0 + 0.51 * i as f64, -10.0 + 0.51 * j as f64);
+                let inside = original.contains_point(point, fill_rule);
+                assert_eq!(resolved.contains_point(point, FillRule::NonZero), inside);
+                assert_eq!(resolved.contains_point(point, FillRule::EvenOdd), inside);
+            }
+        }
+    }
+
+    #[test]
+    fn simple_polygons_have_no_self_intersections() {
+        let square = Path::from_svg("M0 0 H4 V4 H0 Z M10 10 L12 10 L11 12 Z").unwrap();
+        assert_eq!(square.self_intersections(), vec![vec![], vec![]]);
+    }
+
+    #[test]
+    fn finds_the_crossing_of_a_figure_eight() {
+        let intersections = figure_eight().self_intersections();
+        assert_eq!(intersections.len(), 1);
+        assert_eq!(intersections[0].len(), 1);
+        let intersection = intersections[0][0];
+        assert!((intersection.point - point2(1.0, 1.0)).magnitude() < 1e-12);
+        // the first line crosses the closing line
+        assert_eq!(intersection.segments, (0, 2));
+    }
+
+    #[test]
+    fn finds_the_crossings_of_a_star() {
+        let intersections = star().self_intersections();
+        assert_eq!(intersections[0].len(), 5);
+    }
+
+    #[test]
+    fn resolves_a_figure_eight_into_two_loops() {
+        let resolved = figure_eight().resolve_self_intersections(FillRule::NonZero);
+        let polygons = resolved.to_polygons();
+        assert_eq!(polygons.len(), 2);
+        for polygon in &polygons {
+            assert_eq!(polygon.len(), 3);
+            assert!((signed_area(polygon) - 1.0).abs() < 1e-9);
+        }
+        assert_same_region(&figure_eight(), &resolved, FillRule::NonZero);
+    }
+
+    #[test]
+    fn resolves_a_star_with_either_fill_rule() {
+        // non-zero: one outline with 10 corners, wound counter-clockwise
+        let resolved = star().resolve_self_intersections(FillRule::NonZero);
+        let polygons = resolved.to_polygons();
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0].len(), 10);
+        assert!(signed_area(&polygons[0]) > 0.0);
+        assert_same_region(&star(), &resolved, FillRule::NonZero);
+
+        // even-odd: the five tips, which only touch at their corners
+        let resolved = star().resolve_self_intersections(FillRule::EvenOdd);
+        let polygons = resolved.to_polygons();
+        assert_eq!(polygons.len(), 5);
+        for polygon in &polygons {
+            assert_eq!(polygon.len(), 3);
+            assert!(signed_area(polygon) > 0.0);
+        }
+        assert_same_region(&star(), &resolved, FillRule::EvenOdd);
+    }
+
+    #[test]
+    fn holes_are_wound_clockwise() {
+        // a hole drawn in the same direction as the outer square only counts with even-odd
+        let path = Path::from_svg("M-8 -8 H8 V8 H-8 Z M-4 -4 H4 V4 H-4 Z").unwrap();
+        let resolved = path.resolve_self_intersections(FillRule::EvenOdd);
+        let mut areas: Vec<f64> = resolved
+            .to_polygons()
+            .iter()
+            .map(|polygon| signed_area(polygon))
+            .collect();
+        areas.sort_by(f64::total_cmp);
+        assert_eq!(areas, vec![-64.0, 256.0]);
+        assert_same_region(&path, &resolved, FillRule::EvenOdd);
+
+        let resolved = path.resolve_self_intersections(FillRule::NonZero);
+        assert_eq!(resolved.to_polygons().len(), 1);
+    }
+}
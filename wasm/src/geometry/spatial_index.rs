@@ -0,0 +1,639 @@
+// Dynamic AABB tree over the bounds of shapes (or segments) for picking, marquee selection and culling
+
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+    hash::Hash,
+};
+
+use cgmath::Point2;
+
+use super::rect::Rect;
+
+#[derive(Clone, Debug)]
+struct Node<K> {
+    // leaves store their bounds enlarged by the index margin
+    bounds: Rect,
+    parent: Option<usize>,
+    children: Option<(usize, usize)>,
+    key: Option<K>,
+    // leaves have height 0
+    height: usize,
+}
+
+#[derive(Clone, Debug)]
+struct Leaf {
+    node: usize,
+    bounds: Rect,
+}
+
+// Keys are anything hashable, e.g. shape ids or (shape id, segment index) pairs
+#[derive(Clone, Debug)]
+pub struct SpatialIndex<K> {
+    nodes: Vec<Node<K>>,
+    free: Vec<usize>,
+    root: Option<usize>,
+    leaves: HashMap<K, Leaf>,
+    // how far leaf bounds are enlarged so that small edits don't move them in the tree
+    margin: f64,
+}
+
+impl<K: Copy + Eq + Hash> Default for SpatialIndex<K> {
+    fn default() -> Self {
+        SpatialIndex::new()
+    }
+}
+
+impl<K: Copy + Eq + Hash> SpatialIndex<K> {
+    pub fn new() -> SpatialIndex<K> {
+        SpatialIndex::with_margin(0.0)
+    }
+
+    pub fn with_margin(margin: f64) -> SpatialIndex<K> {
+        SpatialIndex {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            root: None,
+            leaves: HashMap::new(),
+            margin: margin.max(0.0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.free.clear();
+        self.root = None;
+        self.leaves.clear();
+    }
+
+    pub fn bounds(&self, key: K) -> Option<Rect> {
+        self.leaves.get(&key).map(|leaf| leaf.bounds)
+    }
+
+    // Inserts the key, or moves it if it is already in the index
+    pub fn insert(&mut self, key: K, bounds: Rect) {
+        if self.leaves.contains_key(&key) {
+            self.update(key, bounds);
+            return;
+        }
+        let node = self.allocate(Node {
+            bounds: bounds.inflate(self.margin),
+            parent: None,
+            children: None,
+            key: Some(key),
+            height: 0,
+        });
+        self.insert_leaf(node);
+        self.leaves.insert(key, Leaf { node, bounds });
+    }
+
+    // Updates the bounds of a key after its shape was edited.
+    // Returns false if the key isn't in the index.
+    pub fn update(&mut self, key: K, bounds: Rect) -> bool {
+        let margin = self.margin;
+        let Some(leaf) = self.leaves.get_mut(&key) else {
+            return false;
+        };
+        leaf.bounds = bounds;
+        let node = leaf.node;
+        // the tree only changes once the shape leaves its enlarged bounds
+        if self.nodes[node].bounds.contains_rect(&bounds) {
+            return true;
+        }
+        self.remove_leaf(node);
+        self.nodes[node].bounds = bounds.inflate(margin);
+        self.insert_leaf(node);
+        true
+    }
+
+    pub fn remove(&mut self, key: K) -> bool {
+        match self.leaves.remove(&key) {
+            Some(leaf) => {
+                self.remove_leaf(leaf.node);
+                self.free.push(leaf.node);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Keys whose bounds contain the point
+    pub fn query_point(&self, point: Point2<f64>) -> Vec<K> {
+        self.query(
+            |bounds| bounds.contains(point),
+            |leaf| leaf.bounds.contains(point),
+        )
+    }
+
+    // Keys whose bounds intersect the rectangle
+    pub fn query_rect(&self, rect: &Rect) -> Vec<K> {
+        self.query(
+            |bounds| bounds.intersects(rect),
+            |leaf| leaf.bounds.intersects(rect),
+        )
+    }
+
+    // Keys whose bounds lie entirely inside the rectangle
+    pub fn query_rect_contained(&self, rect: &Rect) -> Vec<K> {
+        self.query(
+            |bounds| bounds.intersects(rect),
+            |leaf| rect.contains_rect(&leaf.bounds),
+        )
+    }
+
+    // The key closest to the point and its distance, where `distance` computes the exact distance to a key's shape.
+    // The distance must not be smaller than the distance to the key's bounds.
+    pub fn nearest(
+        &self,
+        point: Point2<f64>,
+        mut distance: impl FnMut(K) -> f64,
+    ) -> Option<(K, f64)> {
+        let mut best: Option<(K, f64)> = None;
+        let mut queue = BinaryHeap::new();
+        if let Some(root) = self.root {
+            queue.push(Candidate {
+                distance: self.nodes[root].bounds.distance_to_point(point),
+                node: root,
+            });
+        }
+        while let Some(Candidate {
+            distance: bound,
+            node,
+        }) = queue.pop()
+        {
+            if best.is_some_and(|(_, best)| bound >= best) {
+                break;
+            }
+            let node = &self.nodes[node];
+            match (node.children, node.key) {
+                (Some((child1, child2)), _) => {
+                    for child in [child1, child2] {
+                        queue.push(Candidate {
+                            distance: self.nodes[child].bounds.distance_to_point(point),
+                            node: child,
+                        });
+                    }
+                }
+                (None, Some(key)) => {
+                    let d = distance(key);
+                    if best.is_none_or(|(_, best)| d < best) {
+                        best = Some((key, d));
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+        best
+    }
+
+    // The key with the closest bounds to the point
+    pub fn nearest_bounds(&self, point: Point2<f64>) -> Option<(K, f64)> {
+        self.nearest(point, |key| {
+            self.leaves[&key].bounds.distance_to_point(point)
+        })
+    }
+
+    fn query(&self, visit: impl Fn(&Rect) -> bool, accept: impl Fn(&Leaf) -> bool) -> Vec<K> {
+        let mut keys = Vec::new();
+        let mut stack: Vec<usize> = self.root.into_iter().collect();
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !visit(&node.bounds) {
+                continue;
+            }
+            match (node.children, node.key) {
+                (Some((child1, child2)), _) => {
+                    stack.push(child1);
+                    stack.push(child2);
+                }
+                (None, Some(key)) => {
+                    if accept(&self.leaves[&key]) {
+                        keys.push(key);
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+        keys
+    }
+
+    fn allocate(&mut self, node: Node<K>) -> usize {
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn insert_leaf(&mut self, leaf: usize) {
+        let Some(root) = self.root else {
+            self.nodes[leaf].parent = None;
+            self.root = Some(leaf);
+            return;
+        };
+
+        // descend towards the sibling that increases the total perimeter the least
+        let bounds = self.nodes[leaf].bounds;
+        let mut index = root;
+        while let Some((child1, child2)) = self.nodes[index].children {
+            let node_bounds = self.nodes[index].bounds;
+            let combined = perimeter(&node_bounds.union(&bounds));
+            // cost of making a new parent for this node and the leaf
+            let cost = 2.0 * combined;
+            // minimum cost of pushing the leaf further down
+            let inheritance = 2.0 * (combined - perimeter(&node_bounds));
+            let child_cost = |child: usize| {
+                let child = &self.nodes[child];
+                let union = perimeter(&child.bounds.union(&bounds));
+                if child.children.is_none() {
+                    union + inheritance
+                } else {
+                    union - perimeter(&child.bounds) + inheritance
+                }
+            };
+            let (cost1, cost2) = (child_cost(child1), child_cost(child2));
+            if cost < cost1 && cost < cost2 {
+                break;
+            }
+            index = if cost1 < cost2 { child1 } else { child2 };
+        }
+
+        let sibling = index;
+        let old_parent = self.nodes[sibling].parent;
+        let parent = self.allocate(Node {
+            bounds: self.nodes[sibling].bounds.union(&bounds),
+            parent: old_parent,
+            children: Some((sibling, leaf)),
+            key: None,
+            height: self.nodes[sibling].height + 1,
+        });
+        self.nodes[sibling].parent = Some(parent);
+        self.nodes[leaf].parent = Some(parent);
+        match old_parent {
+            Some(old_parent) => self.replace_child(old_parent, sibling, parent),
+            None => self.root = Some(parent),
+        }
+        self.refit(self.nodes[leaf].parent);
+    }
+
+    fn remove_leaf(&mut self, leaf: usize) {
+        if self.root == Some(leaf) {
+            self.root = None;
+            return;
+        }
+        let parent = self.nodes[leaf].parent.unwrap();
+        let (child1, child2) = self.nodes[parent].children.unwrap();
+        let sibling = if child1 == leaf { child2 } else { child1 };
+        let grandparent = self.nodes[parent].parent;
+        self.nodes[sibling].parent = grandparent;
+        match grandparent {
+            Some(grandparent) => self.replace_child(grandparent, parent, sibling),
+            None => self.root = Some(sibling),
+        }
+        self.free.push(parent);
+        self.nodes[leaf].parent = None;
+        self.refit(grandparent);
+    }
+
+    fn replace_child(&mut self, parent: usize, old: usize, new: usize) {
+        let (child1, child2) = self.nodes[parent].children.unwrap();
+        self.nodes[parent].children = Some(if child1 == old {
+            (new, child2)
+        } else {
+            (child1, new)
+        });
+    }
+
+    // Rebalances and recomputes bounds and heights from the node up to the root
+    fn refit(&mut self, mut index: Option<usize>) {
+        while let Some(node) = index {
+            let node = self.balance(node);
+            let (child1, child2) = self.nodes[node].children.unwrap();
+            self.nodes[node].height = 1 + self.nodes[child1].height.max(self.nodes[child2].height);
+            self.nodes[node].bounds = self.nodes[child1].bounds.union(&self.nodes[child2].bounds);
+            index = self.nodes[node].parent;
+        }
+    }
+
+    // Rotates the taller child up while the subtree is unbalanced and returns the subtree's new root
+    fn balance(&mut self, a: usize) -> usize {
+        let Some((b, c)) = self.nodes[a].children else {
+            return a;
+        };
+        let (height_b, height_c) = (self.nodes[b].height, self.nodes[c].height);
+        let up = if height_c > height_b + 1 {
+            self.rotate_up(a, c, b)
+        } else if height_b > height_c + 1 {
+            self.rotate_up(a, b, c)
+        } else {
+            return a;
+        };
+        // `a` now joins two subtrees whose heights can still differ by more than one,
+        // e.g. after a leaf was paired with a tall subtree
+        self.balance(a);
+        let (child1, child2) = self.nodes[up].children.unwrap();
+        self.nodes[up].height = 1 + self.nodes[child1].height.max(self.nodes[child2].height);
+        self.balance(up)
+    }
+
+    // Makes the child `up` the parent of `a`, which keeps its other child `other`
+    // and adopts the shorter child of `up`
+    fn rotate_up(&mut self, a: usize, up: usize, other: usize) -> usize {
+        let (f, g) = self.nodes[up].children.unwrap();
+        let (taller, shorter) = if self.nodes[f].height > self.nodes[g].height {
+            (f, g)
+        } else {
+            (g, f)
+        };
+
+        let grandparent = self.nodes[a].parent;
+        self.nodes[up].parent = grandparent;
+        match grandparent {
+            Some(grandparent) => self.replace_child(grandparent, a, up),
+            None => self.root = Some(up),
+        }
+
+        self.nodes[a].children = Some((other, shorter));
+        self.nodes[a].parent = Some(up);
+        self.nodes[shorter].parent = Some(a);
+        self.nodes[a].bounds = self.nodes[other].bounds.union(&self.nodes[shorter].bounds);
+        self.nodes[a].height = 1 + self.nodes[other].height.max(self.nodes[shorter].height);
+
+        self.nodes[up].children = Some((a, taller));
+        self.nodes[up].bounds = self.nodes[a].bounds.union(&self.nodes[taller].bounds);
+        self.nodes[up].height = 1 + self.nodes[a].height.max(self.nodes[taller].height);
+        up
+    }
+}
+
+fn perimeter(rect: &Rect) -> f64 {
+    2.0 * (rect.width() + rect.height())
+}
+
+// Min-heap entry for best-first nearest queries
+#[derive(Clone, Copy, Debug)]
+struct Candidate {
+    distance: f64,
+    node: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.total_cmp(&self.distance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{point2, vec2, InnerSpace};
+
+    use super::*;
+
+    // deterministic pseudo-random rects in [0, 1000)^2
+    fn rects(count: usize) -> Vec<Rect> {
+        let mut state = 0x2545f4914f6cdd1du64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        };
+        (0..count)
+            .map(|_| {
+                let min = point2(next() * 1000.0, next() * 1000.0);
+                Rect::new(min, min + vec2(next() * 50.0, next() * 50.0))
+            })
+            .collect()
+    }
+
+    // Checks parent links, bounds, heights and balance of the whole tree and returns its height
+    fn check_tree(index: &SpatialIndex<usize>) -> usize {
+        let Some(root) = index.root else {
+            assert!(index.leaves.is_empty());
+            return 0;
+        };
+        assert_eq!(index.nodes[root].parent, None);
+        let mut leaf_count = 0;
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            let n = &index.nodes[node];
+            match n.children {
+                Some((child1, child2)) => {
+                    let (c1, c2) = (&index.nodes[child1], &index.nodes[child2]);
+                    assert_eq!(c1.parent, Some(node));
+                    assert_eq!(c2.parent, Some(node));
+                    assert_eq!(n.bounds, c1.bounds.union(&c2.bounds));
+                    assert_eq!(n.height, 1 + c1.height.max(c2.height));
+                    assert!(c1.height.abs_diff(c2.height) <= 1, "unbalanced node");
+                    stack.extend([child1, child2]);
+                }
+                None => {
+                    let key = n.key.unwrap();
+                    assert_eq!(n.height, 0);
+                    assert_eq!(index.leaves[&key].node, node);
+                    assert!(n.bounds.contains_rect(&index.leaves[&key].bounds));
+                    leaf_count += 1;
+                }
+            }
+        }
+        assert_eq!(leaf_count, index.len());
+        index.nodes[root].height
+    }
+
+    fn sorted(mut keys: Vec<usize>) -> Vec<usize> {
+        keys.sort();
+        keys
+    }
+
+    fn brute_force(rects: &[Rect], accept: impl Fn(&Rect) -> bool) -> Vec<usize> {
+        (0..rects.len()).filter(|&i| accept(&rects[i])).collect()
+    }
+
+    #[test]
+    fn stays_balanced() {
+        let mut index = SpatialIndex::new();
+        // sorted insertions are the worst case for an unbalanced tree
+        for i in 0..1000 {
+            let min = point2(i as f64, 0.0);
+            index.insert(i, Rect::new(min, min + vec2(1.0, 1.0)));
+        }
+        assert_eq!(index.len(), 1000);
+        // an AVL-balanced binary tree with 1000 leaves is at most ~1.44 log2(1000) high
+        assert!(check_tree(&index) <= 15);
+
+        for i in (0..1000).step_by(2) {
+            assert!(index.remove(i));
+        }
+        assert!(!index.remove(0));
+        assert_eq!(index.len(), 500);
+        check_tree(&index);
+    }
+
+    #[test]
+    fn stays_balanced_under_mixed_edits() {
+        let rects = rects(600);
+        let mut index = SpatialIndex::with_margin(2.0);
+        for (step, rect) in rects.iter().enumerate() {
+            // keys 0..200 get inserted, moved around and removed in turn
+            let key = step % 200;
+            match step / 200 {
+                0 => index.insert(key, *rect),
+                1 => assert!(index.update(key, *rect)),
+                _ => assert!(index.remove(key)),
+            }
+            check_tree(&index);
+        }
+        assert!(index.is_empty());
+    }
+
+    #[test]
+    fn queries_match_brute_force() {
+        let rects = rects(300);
+        let mut index = SpatialIndex::new();
+        for (key, rect) in rects.iter().enumerate() {
+            index.insert(key, *rect);
+        }
+        check_tree(&index);
+
+        for point in [point2(500.0, 500.0), point2(10.0, 990.0), point2(-5.0, 0.0)] {
+            assert_eq!(
+                sorted(index.query_point(point)),
+                brute_force(&rects, |rect| rect.contains(point))
+            );
+        }
+        for area in [
+            Rect::new(point2(100.0, 100.0), point2(300.0, 250.0)),
+            Rect::new(point2(0.0, 0.0), point2(1000.0, 1000.0)),
+            Rect::new(point2(2000.0, 0.0), point2(3000.0, 10.0)),
+        ] {
+            assert_eq!(
+                sorted(index.query_rect(&area)),
+                brute_force(&rects, |rect| rect.intersects(&area))
+            );
+            assert_eq!(
+                sorted(index.query_rect_contained(&area)),
+                brute_force(&rects, |rect| area.contains_rect(rect))
+            );
+        }
+    }
+
+    #[test]
+    fn nearest_matches_brute_force() {
+        let rects = rects(300);
+        let mut index = SpatialIndex::new();
+        assert_eq!(index.nearest_bounds(point2(0.0, 0.0)), None);
+        for (key, rect) in rects.iter().enumerate() {
+            index.insert(key, *rect);
+        }
+
+        for point in [
+            point2(500.0, 500.0),
+            point2(-100.0, 40.0),
+            point2(1200.0, 1200.0),
+        ] {
+            // exact distance: to the rect's center, which is never closer than its bounds
+            let distance = |key: usize| (rects[key].center() - point).magnitude();
+            let expected = (0..rects.len())
+                .map(|key| (key, distance(key)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap();
+            assert_eq!(index.nearest(point, distance), Some(expected));
+
+            let (key, d) = index.nearest_bounds(point).unwrap();
+            let closest = rects
+                .iter()
+                .map(|rect| rect.distance_to_point(point))
+                .fold(f64::INFINITY, f64::min);
+            assert_eq!(d, closest);
+            assert_eq!(rects[key].distance_to_point(point), closest);
+        }
+    }
+
+    #[test]
+    fn updates_within_the_margin_keep_the_tree() {
+        let mut index = SpatialIndex::with_margin(5.0);
+        for (key, rect) in rects(50).into_iter().enumerate() {
+            index.insert(key, rect);
+        }
+        let node = index.leaves[&7].node;
+        let enlarged = index.nodes[node].bounds;
+
+        // a small move stays inside the enlarged bounds: only the exact bounds change
+        let moved = Rect::new(enlarged.min + vec2(1.0, 1.0), enlarged.min + vec2(3.0, 3.0));
+        assert!(index.update(7, moved));
+        assert_eq!(index.leaves[&7].node, node);
+        assert_eq!(index.nodes[node].bounds, enlarged);
+        assert_eq!(index.bounds(7), Some(moved));
+        // queries use the exact bounds, not the enlarged ones
+        assert!(!index.query_point(enlarged.min).contains(&7));
+        assert!(index.query_point(moved.center()).contains(&7));
+        check_tree(&index);
+
+        // a large move reinserts the leaf with new enlarged bounds
+        let far = Rect::new(point2(5000.0, 5000.0), point2(5010.0, 5010.0));
+        assert!(index.update(7, far));
+        assert_eq!(index.nodes[index.leaves[&7].node].bounds, far.inflate(5.0));
+        assert_eq!(index.query_point(point2(5005.0, 5005.0)), vec![7]);
+        assert!(!index.query_point(moved.center()).contains(&7));
+        check_tree(&index);
+
+        // inserting an existing key moves it too, unknown keys can't be updated
+        index.insert(7, moved);
+        assert_eq!(index.len(), 50);
+        assert!(index.query_point(point2(5005.0, 5005.0)).is_empty());
+        assert!(!index.update(99, far));
+        check_tree(&index);
+    }
+
+    #[test]
+    fn removing_every_key_empties_the_index() {
+        let mut index = SpatialIndex::new();
+        for (key, rect) in rects(20).into_iter().enumerate() {
+            index.insert(key, rect);
+        }
+        for key in 0..20 {
+            assert!(index.remove(key));
+            check_tree(&index);
+        }
+        assert!(index.is_empty());
+        assert_eq!(index.root, None);
+        assert!(index
+            .query_rect(&Rect::new(point2(0.0, 0.0), point2(1000.0, 1000.0)))
+            .is_empty());
+
+        // freed nodes are reused
+        let node_count = index.nodes.len();
+        for (key, rect) in rects(20).into_iter().enumerate() {
+            index.insert(key, rect);
+        }
+        assert_eq!(index.nodes.len(), node_count);
+        check_tree(&index);
+    }
+}
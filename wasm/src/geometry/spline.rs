@@ -0,0 +1,427 @@
+// Interpolating and approximating splines converted to cubic Bézier paths
+
+use std::{cmp::Ordering, fmt};
+
+use cgmath::{point2, EuclideanSpace, InnerSpace, Point2, Vector2};
+
+use super::path::Path;
+
+pub trait Spline {
+    // Appends the spline as a new subpath of cubic curves
+    fn append_to(&self, path: &mut Path);
+
+    fn to_path(&self) -> Path {
+        let mut path = Path::new();
+        self.append_to(&mut path);
+        path
+    }
+}
+
+// Catmull-Rom spline through the points.
+// `alpha` selects the parameterization: 0 uniform, 0.5 centripetal (no cusps or self-intersections
+// within a segment), 1 chordal.
+#[derive(Clone, Debug)]
+pub struct CatmullRom {
+    pub points: Vec<Point2<f64>>,
+    pub alpha: f64,
+    pub closed: bool,
+}
+
+impl CatmullRom {
+    pub fn uniform(points: Vec<Point2<f64>>) -> CatmullRom {
+        CatmullRom {
+            points,
+            alpha: 0.0,
+            closed: false,
+        }
+    }
+
+    pub fn centripetal(points: Vec<Point2<f64>>) -> CatmullRom {
+        CatmullRom {
+            points,
+            alpha: 0.5,
+            closed: false,
+        }
+    }
+
+    pub fn chordal(points: Vec<Point2<f64>>) -> CatmullRom {
+        CatmullRom {
+            points,
+            alpha: 1.0,
+            closed: false,
+        }
+    }
+
+    pub fn closed(self) -> CatmullRom {
+        CatmullRom {
+            closed: true,
+            ..self
+        }
+    }
+}
+
+impl Spline for CatmullRom {
+    fn append_to(&self, path: &mut Path) {
+        let points = &self.points;
+        let n = points.len();
+        if n < 2 {
+            return;
+        }
+        // open ends get phantom neighbours reflected across the end points
+        let point = |i: isize| -> Point2<f64> {
+            if self.closed {
+                points[i.rem_euclid(n as isize) as usize]
+            } else if i < 0 {
+                points[0] + (points[0] - points[1])
+            } else if i >= n as isize {
+                points[n - 1] + (points[n - 1] - points[n - 2])
+            } else {
+                points[i as usize]
+            }
+        };
+        // knot interval between two points, guarded against coincident points
+        let interval = |a: Point2<f64>, b: Point2<f64>| -> f64 {
+            let d = (b - a).magnitude().powf(self.alpha);
+            if d > 1e-12 {
+                d
+            } else {
+                1.0
+            }
+        };
+
+        path.move_to(points[0]);
+        let count = if self.closed { n } else { n - 1 };
+        for i in 0..count as isize {
+            let (p0, p1, p2, p3) = (point(i - 1), point(i), point(i + 1), point(i + 2));
+            let (d01, d12, d23) = (interval(p0, p1), interval(p1, p2), interval(p2, p3));
+
+            // tangents of the Barry-Goldman formulation at p1 and p2, scaled to the segment interval
+            let m1 = ((p1 - p0) / d01 - (p2 - p0) / (d01 + d12) + (p2 - p1) / d12) * d12;
+            let m2 = ((p2 - p1) / d12 - (p3 - p1) / (d12 + d23) + (p3 - p2) / d23) * d12;
+            path.cubic_to(p1 + m1 / 3.0, p2 - m2 / 3.0, p2);
+        }
+        if self.closed {
+            path.close();
+        }
+    }
+}
+
+// Uniform cubic B-spline approximating the control points.
+// Open splines are clamped by repeating the end points, so the curve starts and ends on them.
+// Closed splines wrap around and have one segment per control point; with only two control points
+// the curve runs along the line between them and back.
+#[derive(Clone, Debug)]
+pub struct BSpline {
+    pub control_points: Vec<Point2<f64>>,
+    pub closed: bool,
+}
+
+impl BSpline {
+    pub fn new(control_points: Vec<Point2<f64>>) -> BSpline {
+        BSpline {
+            control_points,
+            closed: false,
+        }
+    }
+
+    pub fn closed(self) -> BSpline {
+        BSpline {
+            closed: true,
+            ..self
+        }
+    }
+}
+
+impl Spline for BSpline {
+    fn append_to(&self, path: &mut Path) {
+        let n = self.control_points.len();
+        if n < 2 {
+            return;
+        }
+        let points: Vec<Vector2<f64>> = if self.closed {
+            let mut points: Vec<_> = self.control_points.iter().map(|p| p.to_vec()).collect();
+            points.extend(
+                self.control_points
+                    .iter()
+                    .cycle()
+                    .take(3)
+                    .map(|p| p.to_vec()),
+            );
+            points
+        } else {
+            let first = self.control_points[0].to_vec();
+            let last = self.control_points[n - 1].to_vec();
+            [first, first]
+                .into_iter()
+                .chain(self.control_points.iter().map(|p| p.to_vec()))
+                .chain([last, last])
+                .collect()
+        };
+
+        let mut started = false;
+        for window in points.windows(4) {
+            let [b0, b1, b2, b3] = [window[0], window[1], window[2], window[3]];
+            let start = Point2::from_vec((b0 + b1 * 4.0 + b2) / 6.0);
+            if !started {
+                path.move_to(start);
+                started = true;
+            }
+            path.cubic_to(
+                Point2::from_vec((b1 * 2.0 + b2) / 3.0),
+                Point2::from_vec((b1 + b2 * 2.0) / 3.0),
+                Point2::from_vec((b1 + b2 * 4.0 + b3) / 6.0),
+            );
+        }
+        if self.closed {
+            path.close();
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SplineError {
+    NonFinitePoint { point_index: usize },
+    // data points of a monotone cubic have to be sorted by x
+    UnsortedPoint { point_index: usize },
+}
+
+impl fmt::Display for SplineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SplineError::NonFinitePoint { point_index } => {
+                write!(f, "point {} has a non-finite coordinate", point_index)
+            }
+            SplineError::UnsortedPoint { point_index } => {
+                write!(
+                    f,
+                    "point {} does not have a larger x than the previous point",
+                    point_index
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SplineError {}
+
+// Monotone cubic interpolation (Fritsch-Carlson) of data points sorted by x.
+// The curve never overshoots the data, which suits line charts.
+#[derive(Clone, Debug)]
+pub struct MonotoneCubic {
+    points: Vec<Point2<f64>>,
+}
+
+impl MonotoneCubic {
+    // Fails with `SplineError::NonFinitePoint` at the index of a NaN or infinite point
+    // and with `SplineError::UnsortedPoint` if x is not strictly increasing
+    pub fn new(points: Vec<Point2<f64>>) -> Result<MonotoneCubic, SplineError> {
+        if let Some(point_index) = points
+            .iter()
+            .position(|point| !point.x.is_finite() || !point.y.is_finite())
+        {
+            return Err(SplineError::NonFinitePoint { point_index });
+        }
+        for i in 1..points.len() {
+            if points[i].x.partial_cmp(&points[i - 1].x) != Some(Ordering::Greater) {
+                return Err(SplineError::UnsortedPoint { point_index: i });
+            }
+        }
+        Ok(MonotoneCubic { points })
+    }
+
+    pub fn points(&self) -> &[Point2<f64>] {
+        &self.points
+    }
+
+    fn tangents(&self) -> Vec<f64> {
+        let points = &self.points;
+        let n = points.len();
+        let slopes: Vec<f64> = points
+            .windows(2)
+            .map(|w| (w[1].y - w[0].y) / (w[1].x - w[0].x))
+            .collect();
+
+        let mut tangents = vec![0.0; n];
+        tangents[0] = slopes[0];
+        tangents[n - 1] = slopes[n - 2];
+        for i in 1..n - 1 {
+            if slopes[i - 1] * slopes[i] > 0.0 {
+                tangents[i] = (slopes[i - 1] + slopes[i]) / 2.0;
+            }
+        }
+
+        for (i, &slope) in slopes.iter().enumerate() {
+            if slope == 0.0 {
+                tangents[i] = 0.0;
+                tangents[i + 1] = 0.0;
+                continue;
+            }
+            let a = tangents[i] / slope;
+            let b = tangents[i + 1] / slope;
+            let length = (a * a + b * b).sqrt();
+            if length > 3.0 {
+                let tau = 3.0 / length;
+                tangents[i] = tau * a * slope;
+                tangents[i + 1] = tau * b * slope;
+            }
+        }
+        tangents
+    }
+}
+
+impl Spline for MonotoneCubic {
+    fn append_to(&self, path: &mut Path) {
+        let points = &self.points;
+        if points.len() < 2 {
+            return;
+        }
+        let tangents = self.tangents();
+        path.move_to(points[0]);
+        for i in 0..points.len() - 1 {
+            let (p0, p1) = (points[i], points[i + 1]);
+            let h = (p1.x - p0.x) / 3.0;
+            path.cubic_to(
+                point2(p0.x + h, p0.y + tangents[i] * h),
+                point2(p1.x - h, p1.y - tangents[i + 1] * h),
+                p1,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{path::PathCommand, segment::Segment};
+
+    fn points() -> Vec<Point2<f64>> {
+        vec![
+            point2(0.0, 0.0),
+            point2(1.0, 2.0),
+            point2(3.0, 3.0),
+            point2(4.0, 0.0),
+            point2(6.0, 1.0),
+        ]
+    }
+
+    fn cubics(path: &Path) -> Vec<Segment> {
+        let segments = path.to_segments();
+        assert!(segments.iter().all(|s| matches!(s, Segment::Cubic(..))));
+        segments
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_the_points() {
+        for spline in [
+            CatmullRom::uniform(points()),
+            CatmullRom::centripetal(points()),
+            CatmullRom::chordal(points()),
+        ] {
+            let segments = cubics(&spline.to_path());
+            assert_eq!(segments.len(), 4);
+            for (segment, window) in segments.iter().zip(points().windows(2)) {
+                assert_eq!(segment.start(), window[0]);
+                assert_eq!(segment.end(), window[1]);
+            }
+            // consecutive segments join smoothly
+            for pair in segments.windows(2) {
+                let (a, b) = (pair[0].end_tangent(), pair[1].start_tangent());
+                assert!(a.angle(b).0.abs() < 1e-9);
+            }
+
+            let closed = spline.closed().to_path();
+            assert_eq!(closed.commands().last(), Some(&PathCommand::Close));
+            let segments = cubics(&closed);
+            assert_eq!(segments.len(), 5);
+            assert_eq!(segments[4].end(), points()[0]);
+        }
+    }
+
+    #[test]
+    fn b_spline_is_clamped_to_the_end_points() {
+        let segments = cubics(&BSpline::new(points()).to_path());
+        assert_eq!(segments.len(), 6);
+        assert_eq!(segments[0].start(), points()[0]);
+        assert_eq!(segments[5].end(), points()[4]);
+        // interior control points are only approximated
+        assert!(segments.iter().all(|s| s.end() != points()[2]));
+    }
+
+    #[test]
+    fn closed_b_spline_has_one_segment_per_control_point() {
+        for n in 2..=5 {
+            let path = BSpline::new(points()[..n].to_vec()).closed().to_path();
+            let segments = cubics(&path);
+            assert_eq!(segments.len(), n);
+            assert!((segments[n - 1].end() - segments[0].start()).magnitude() < 1e-12);
+        }
+
+        // two control points: along the line between them and back
+        let path = BSpline::new(vec![point2(0.0, 0.0), point2(6.0, 3.0)])
+            .closed()
+            .to_path();
+        let segments = cubics(&path);
+        assert_eq!(segments[0].start(), point2(4.0, 2.0));
+        assert_eq!(segments[0].end(), point2(2.0, 1.0));
+        assert_eq!(segments[1].end(), point2(4.0, 2.0));
+    }
+
+    #[test]
+    fn monotone_cubic_interpolates_without_overshooting() {
+        let data = vec![
+            point2(0.0, 0.0),
+            point2(1.0, 0.1),
+            point2(2.0, 2.0),
+            point2(3.0, 2.0),
+            point2(5.0, 2.5),
+            point2(6.0, 10.0),
+        ];
+        let segments = cubics(&MonotoneCubic::new(data.clone()).unwrap().to_path());
+        assert_eq!(segments.len(), 5);
+        for (segment, window) in segments.iter().zip(data.windows(2)) {
+            assert_eq!(segment.start(), window[0]);
+            assert_eq!(segment.end(), window[1]);
+
+            // both x and y are non-decreasing along each segment, and y stays within the data range
+            let samples: Vec<_> = (0..=100)
+                .map(|i| segment.point_at(i as f64 / 100.0))
+                .collect();
+            for pair in samples.windows(2) {
+                assert!(pair[1].x >= pair[0].x);
+                assert!(pair[1].y >= pair[0].y - 1e-12);
+            }
+            assert!(samples
+                .iter()
+                .all(|p| p.y >= window[0].y - 1e-12 && p.y <= window[1].y + 1e-12));
+        }
+    }
+
+    #[test]
+    fn monotone_cubic_rejects_unsorted_points() {
+        let data = vec![point2(0.0, 0.0), point2(2.0, 1.0), point2(1.0, 2.0)];
+        assert!(matches!(
+            MonotoneCubic::new(data),
+            Err(SplineError::UnsortedPoint { point_index: 2 })
+        ));
+        assert_eq!(
+            SplineError::UnsortedPoint { point_index: 2 }.to_string(),
+            "point 2 does not have a larger x than the previous point"
+        );
+    }
+
+    #[test]
+    fn monotone_cubic_rejects_non_finite_points() {
+        assert!(matches!(
+            MonotoneCubic::new(vec![point2(0.0, f64::NAN), point2(1.0, 1.0)]),
+            Err(SplineError::NonFinitePoint { point_index: 0 })
+        ));
+        assert!(matches!(
+            MonotoneCubic::new(vec![point2(0.0, 0.0), point2(f64::INFINITY, 1.0)]),
+            Err(SplineError::NonFinitePoint { point_index: 1 })
+        ));
+        assert_eq!(
+            SplineError::NonFinitePoint { point_index: 1 }.to_string(),
+            "point 1 has a non-finite coordinate"
+        );
+    }
+}
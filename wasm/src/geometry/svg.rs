@@ -0,0 +1,310 @@
+// Parsing SVG path data (the `d` attribute), including relative, smooth and arc commands
+
+use std::fmt;
+
+use cgmath::{point2, vec2};
+
+use super::{error::PathError, path::Path};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SvgError {
+    // byte offset into the path data where it can't be parsed
+    Syntax { position: usize },
+    // well-formed path data that describes an invalid path
+    Path(PathError),
+}
+
+impl fmt::Display for SvgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SvgError::Syntax { position } => {
+                write!(f, "invalid SVG path data at byte {}", position)
+            }
+            SvgError::Path(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for SvgError {}
+
+impl From<PathError> for SvgError {
+    fn from(error: PathError) -> SvgError {
+        SvgError::Path(error)
+    }
+}
+
+impl Path {
+    pub fn from_svg(data: &str) -> Result<Path, SvgError> {
+        let mut parser = Parser {
+            data: data.as_bytes(),
+            position: 0,
+        };
+        let mut path = Path::new();
+        let mut command: Option<u8> = None;
+        let mut has_move_to = false;
+
+        loop {
+            parser.skip_separators();
+            let Some(&next) = parser.data.get(parser.position) else {
+                break;
+            };
+            if next.is_ascii_alphabetic() {
+                command = Some(next);
+                parser.position += 1;
+            } else if matches!(command, None | Some(b'Z' | b'z')) {
+                // numbers have to follow a command that takes arguments
+                return Err(parser.error());
+            }
+            let letter = command.unwrap();
+            if !has_move_to && !letter.eq_ignore_ascii_case(&b'm') {
+                return Err(SvgError::Path(PathError::MissingMoveTo {
+                    command_index: path.commands().len(),
+                }));
+            }
+
+            match letter {
+                b'M' => {
+                    path.move_to(point2(parser.number()?, parser.number()?));
+                    has_move_to = true;
+                    // further coordinate pairs are implicit line commands
+                    command = Some(b'L');
+                }
+                b'm' => {
+                    path.relative_move_to(vec2(parser.number()?, parser.number()?));
+                    has_move_to = true;
+                    command = Some(b'l');
+                }
+                b'L' => path.line_to(point2(parser.number()?, parser.number()?)),
+                b'l' => path.relative_line_to(vec2(parser.number()?, parser.number()?)),
+                b'H' => path.horizontal_line_to(parser.number()?),
+                b'h' => path.relative_horizontal_line_to(parser.number()?),
+                b'V' => path.vertical_line_to(parser.number()?),
+                b'v' => path.relative_vertical_line_to(parser.number()?),
+                b'Q' => path.quad_to(
+                    point2(parser.number()?, parser.number()?),
+                    point2(parser.number()?, parser.number()?),
+                ),
+                b'q' => path.relative_quad_to(
+                    vec2(parser.number()?, parser.number()?),
+                    vec2(parser.number()?, parser.number()?),
+                ),
+                b'T' => path.smooth_quad_to(point2(parser.number()?, parser.number()?)),
+                b't' => path.relative_smooth_quad_to(vec2(parser.number()?, parser.number()?)),
+                b'C' => path.cubic_to(
+                    point2(parser.number()?, parser.number()?),
+                    point2(parser.number()?, parser.number()?),
+                    point2(parser.number()?, parser.number()?),
+                ),
+                b'c' => path.relative_cubic_to(
+                    vec2(parser.number()?, parser.number()?),
+                    vec2(parser.number()?, parser.number()?),
+                    vec2(parser.number()?, parser.number()?),
+                ),
+                b'S' => path.smooth_cubic_to(
+                    point2(parser.number()?, parser.number()?),
+                    point2(parser.number()?, parser.number()?),
+                ),
+                b's' => path.relative_smooth_cubic_to(
+                    vec2(parser.number()?, parser.number()?),
+                    vec2(parser.number()?, parser.number()?),
+                ),
+                b'A' | b'a' => {
+                    let radii = vec2(parser.number()?, parser.number()?);
+                    let x_axis_rotation = parser.number()?;
+                    let large_arc = parser.flag()?;
+                    let sweep = parser.flag()?;
+                    let (x, y) = (parser.number()?, parser.number()?);
+                    if letter == b'A' {
+                        path.arc_to(radii, x_axis_rotation, large_arc, sweep, point2(x, y));
+                    } else {
+                        path.relative_arc_to(radii, x_axis_rotation, large_arc, sweep, vec2(x, y));
+                    }
+                }
+                b'Z' | b'z' => path.close(),
+                _ => {
+                    return Err(SvgError::Syntax {
+                        position: parser.position - 1,
+                    })
+                }
+            }
+        }
+
+        path.validate()?;
+        Ok(path)
+    }
+}
+
+struct Parser<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self) -> SvgError {
+        SvgError::Syntax {
+            position: self.position,
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        while let Some(byte) = self.data.get(self.position) {
+            if !byte.is_ascii_whitespace() && *byte != b',' {
+                break;
+            }
+            self.position += 1;
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, SvgError> {
+        self.skip_separators();
+        let start = self.position;
+        let digits = |parser: &mut Parser| {
+            let start = parser.position;
+            while parser
+                .data
+                .get(parser.position)
+                .is_some_and(u8::is_ascii_digit)
+            {
+                parser.position += 1;
+            }
+            parser.position > start
+        };
+
+        if matches!(self.data.get(self.position), Some(b'+' | b'-')) {
+            self.position += 1;
+        }
+        let mut has_digits = digits(self);
+        if self.data.get(self.position) == Some(&b'.') {
+            self.position += 1;
+            has_digits |= digits(self);
+        }
+        if !has_digits {
+            self.position = start;
+            return Err(self.error());
+        }
+        // an exponent, unless the `e` starts something else
+        if matches!(self.data.get(self.position), Some(b'e' | b'E')) {
+            let mantissa_end = self.position;
+            self.position += 1;
+            if matches!(self.data.get(self.position), Some(b'+' | b'-')) {
+                self.position += 1;
+            }
+            if !digits(self) {
+                self.position = mantissa_end;
+            }
+        }
+
+        // the slice only contains ASCII digits, signs, dots and exponents
+        std::str::from_utf8(&self.data[start..self.position])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .ok_or(SvgError::Syntax { position: start })
+    }
+
+    // Arc flags are single digits that don't need separators, e.g. "a1 1 0 00 1 1"
+    fn flag(&mut self) -> Result<bool, SvgError> {
+        self.skip_separators();
+        let flag = match self.data.get(self.position) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(self.error()),
+        };
+        self.position += 1;
+        Ok(flag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::InnerSpace;
+
+    use super::*;
+    use crate::geometry::path::PathCommand;
+
+    fn assert_parses_like(data: &str, expected: Path) {
+        assert_eq!(
+            Path::from_svg(data).unwrap().commands(),
+            expected.commands()
+        );
+    }
+
+    #[test]
+    fn implicit_repeated_commands() {
+        let mut path = Path::new();
+        path.move_to(point2(0.0, 0.0));
+        path.line_to(point2(10.0, 0.0));
+        path.line_to(point2(10.0, 10.0));
+        path.relative_move_to(vec2(1.0, 1.0));
+        path.relative_line_to(vec2(2.0, 0.0));
+        path.relative_line_to(vec2(0.0, 2.0));
+        path.cubic_to(point2(1.0, 0.0), point2(2.0, 1.0), point2(2.0, 2.0));
+        path.cubic_to(point2(3.0, 3.0), point2(4.0, 3.0), point2(5.0, 2.0));
+        path.horizontal_line_to(1.0);
+        path.horizontal_line_to(3.0);
+        path.close();
+        assert_parses_like(
+            "M0 0 10 0 10 10 m1 1 2 0 0 2 C1 0 2 1 2 2 3 3 4 3 5 2 H1 3z",
+            path,
+        );
+    }
+
+    #[test]
+    fn numbers_with_exponents_and_without_separators() {
+        let mut path = Path::new();
+        path.move_to(point2(100.0, -0.25));
+        path.line_to(point2(0.5, 0.5));
+        path.line_to(point2(-1.0, -2.0));
+        path.line_to(point2(0.01, 3.0));
+        assert_parses_like("M1e2-2.5E-1L.5.5-1-2,1e-2,+3", path);
+    }
+
+    #[test]
+    fn arcs() {
+        let mut path = Path::new();
+        path.move_to(point2(0.0, 0.0));
+        path.arc_to(vec2(1.0, 1.0), 0.0, false, true, point2(2.0, 0.0));
+        path.relative_arc_to(vec2(2.0, 1.0), 30.0, true, false, vec2(-2.0, 0.0));
+        path.relative_arc_to(vec2(1.0, 1.0), 0.0, false, true, vec2(2.0, 0.0));
+        // compact flags without separators
+        assert_parses_like("M0 0A1 1 0 0 1 2 0a2 1 30 10-2 0 1 1 0 012 0", path);
+
+        let path = Path::from_svg("M0 0A1 1 0 0 1 2 0").unwrap();
+        let Some(PathCommand::CubicTo(_, _, end)) = path.commands().last() else {
+            panic!("expected the arc to end with a cubic");
+        };
+        assert!((end - point2(2.0, 0.0)).magnitude() < 1e-12);
+    }
+
+    #[test]
+    fn error_positions() {
+        for (data, position) in [
+            // a letter that isn't a command
+            ("M0 0 L1 x", 8),
+            // missing coordinate at the end of the data
+            ("M0 0 L1", 7),
+            // an implicit line with a single coordinate
+            ("M0 0 1 1 2", 10),
+            // numbers after a close
+            ("M0 0 h1 z 1 1", 10),
+            // an arc flag that isn't 0 or 1
+            ("M0 0 A1 1 0 2 0 1 1", 12),
+            // a sign without digits, reported at the start of the number
+            ("M0 0 L- 1", 6),
+        ] {
+            assert_eq!(
+                Path::from_svg(data).unwrap_err(),
+                SvgError::Syntax { position },
+                "{}",
+                data
+            );
+        }
+        assert_eq!(
+            Path::from_svg("L1 1").unwrap_err(),
+            SvgError::Path(PathError::MissingMoveTo { command_index: 0 })
+        );
+        assert_eq!(
+            Path::from_svg("M0 0 L1 x").unwrap_err().to_string(),
+            "invalid SVG path data at byte 8"
+        );
+    }
+}
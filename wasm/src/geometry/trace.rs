@@ -0,0 +1,462 @@
+// Bitmap tracing: marching squares contours followed by cubic curve fitting
+
+use std::{collections::HashMap, f64::consts::PI, fmt};
+
+use cgmath::{point2, InnerSpace, Point2};
+
+use super::{fitting::fit_cubics_with_tangents, path::Path, segment::Segment};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Bitmap<'a> {
+    // one byte per pixel, row-major
+    data: &'a [u8],
+    width: usize,
+    height: usize,
+}
+
+impl<'a> Bitmap<'a> {
+    pub fn new(data: &'a [u8], width: usize, height: usize) -> Result<Bitmap<'a>, BitmapError> {
+        if data.len() != width * height {
+            return Err(BitmapError::SizeMismatch {
+                expected: width * height,
+                actual: data.len(),
+            });
+        }
+        Ok(Bitmap {
+            data,
+            width,
+            height,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+}
+
+// Bitmap data that doesn't have one byte per pixel
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BitmapError {
+    SizeMismatch { expected: usize, actual: usize },
+}
+
+impl fmt::Display for BitmapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BitmapError::SizeMismatch { expected, actual } => {
+                write!(f, "expected {} bitmap bytes but got {}", expected, actual)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BitmapError {}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TraceOptions {
+    // pixels with a value >= threshold are inside the traced shape
+    pub threshold: u8,
+    // trace dark pixels instead of bright ones
+    pub invert: bool,
+    // number of smoothing passes applied to the outline before curve fitting
+    pub smoothing: usize,
+    // max distance in pixels between the outline and the fitted curves
+    pub tolerance: f64,
+    // turns sharper than this angle (radians) are kept as corners
+    pub corner_threshold: f64,
+    // contours enclosing less area (in square pixels) are dropped as noise
+    pub min_area: f64,
+}
+
+impl Default for TraceOptions {
+    fn default() -> Self {
+        TraceOptions {
+            threshold: 128,
+            invert: false,
+            smoothing: 2,
+            tolerance: 0.5,
+            corner_threshold: PI / 3.0,
+            min_area: 2.0,
+        }
+    }
+}
+
+// Traces the bitmap into a path in pixel coordinates (origin at the top-left corner).
+// Outer contours and holes have opposite orientations, so the result is filled with the nonzero rule.
+pub fn trace_bitmap(bitmap: &Bitmap, options: &TraceOptions) -> Path {
+    let mut path = Path::new();
+    for contour in marching_squares(bitmap, options) {
+        if signed_area(&contour).abs() < options.min_area {
+            continue;
+        }
+        let corners = find_corners(&contour, options.corner_threshold);
+        let contour = sharpen_corners(&contour, &corners);
+        let contour = smooth(&contour, &corners, options.smoothing);
+        append_fitted_contour(&mut path, &contour, &corners, options.tolerance);
+    }
+    path
+}
+
+// Closed contours through the threshold level, with the inside on the left
+fn marching_squares(bitmap: &Bitmap, options: &TraceOptions) -> Vec<Vec<Point2<f64>>> {
+    let width = bitmap.width as i64;
+    let height = bitmap.height as i64;
+    let level = options.threshold as f64 - 0.5;
+    // samples are at pixel centers, padded with an outside border so that every contour closes
+    let sample = |x: i64, y: i64| -> f64 {
+        if x < 0 || y < 0 || x >= width || y >= height {
+            return -1.0;
+        }
+        let value = bitmap.data[(y * width + x) as usize] as f64;
+        if options.invert {
+            255.0 - value
+        } else {
+            value
+        }
+    };
+
+    // crossings are keyed by the grid edge they lie on: (x, y, 0) horizontal, (x, y, 1) vertical
+    let crossing = |a: (i64, i64), b: (i64, i64)| -> ((i64, i64, u8), Point2<f64>) {
+        let (va, vb) = (sample(a.0, a.1), sample(b.0, b.1));
+        let t = (level - va) / (vb - va);
+        let position = point2(
+            a.0 as f64 + (b.0 - a.0) as f64 * t + 0.5,
+            a.1 as f64 + (b.1 - a.1) as f64 * t + 0.5,
+        );
+        let key = (a.0.min(b.0), a.1.min(b.1), (a.0 == b.0) as u8);
+        (key, position)
+    };
+
+    let mut next = HashMap::new();
+    let mut positions = HashMap::new();
+    for y in -1..height {
+        for x in -1..width {
+            // cell corners in counter-clockwise order (in y-up terms)
+            let corners = [(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)];
+            let inside = corners.map(|(cx, cy)| sample(cx, cy) > level);
+
+            // crossings where the boundary enters (outside -> inside) and leaves the inside
+            let mut entries = Vec::new();
+            let mut exits = Vec::new();
+            for i in 0..4 {
+                let j = (i + 1) % 4;
+                if inside[i] != inside[j] {
+                    let (key, position) = crossing(corners[i], corners[j]);
+                    positions.insert(key, position);
+                    if inside[j] {
+                        entries.push(key);
+                    } else {
+                        exits.push(key);
+                    }
+                }
+            }
+
+            match entries.len() {
+                1 => {
+                    next.insert(exits[0], entries[0]);
+                }
+                2 => {
+                    // saddle: use the cell center to decide whether the inside corners are connected
+                    let center = corners.iter().map(|&(cx, cy)| sample(cx, cy)).sum::<f64>() / 4.0;
+                    // entries and exits alternate around the cell; pair each exit with the next entry
+                    // when the center is inside, otherwise with the previous one
+                    let first_is_entry = (0..4)
+                        .find(|&i| inside[i] != inside[(i + 1) % 4])
+                        .map(|i| inside[(i + 1) % 4])
+                        .unwrap();
+                    let connect_center = center > level;
+                    let (e0, e1, x0, x1) = if first_is_entry {
+                        (entries[0], entries[1], exits[0], exits[1])
+                    } else {
+                        (entries[1], entries[0], exits[0], exits[1])
+                    };
+                    // order around the cell: e0, x0, e1, x1
+                    if connect_center {
+                        next.insert(x0, e1);
+                        next.insert(x1, e0);
+                    } else {
+                        next.insert(x0, e0);
+                        next.insert(x1, e1);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut contours = Vec::new();
+    let mut keys: Vec<_> = next.keys().copied().collect();
+    keys.sort();
+    for start in keys {
+        if !next.contains_key(&start) {
+            continue;
+        }
+        let mut contour = Vec::new();
+        let mut key = start;
+        while let Some(following) = next.remove(&key) {
+            contour.push(positions[&key]);
+            key = following;
+        }
+        if contour.len() >= 3 {
+            contours.push(contour);
+        }
+    }
+    contours
+}
+
+fn signed_area(points: &[Point2<f64>]) -> f64 {
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f64>()
+        / 2.0
+}
+
+// Indices of points where the contour turns sharper than `threshold`
+fn find_corners(points: &[Point2<f64>], threshold: f64) -> Vec<usize> {
+    let n = points.len();
+    // measure the turn over two neighbours on each side, so that staircase steps are not corners
+    let reach = 2.min((n - 1) / 2);
+    if reach == 0 {
+        return Vec::new();
+    }
+    let angles: Vec<f64> = (0..n)
+        .map(|i| {
+            let incoming = points[i] - points[(i + n - reach) % n];
+            let outgoing = points[(i + reach) % n] - points[i];
+            incoming.angle(outgoing).0.abs()
+        })
+        .collect();
+    // keep local maxima only
+    (0..n)
+        .filter(|&i| {
+            angles[i] > threshold
+                && (1..=reach).all(|d| angles[i] >= angles[(i + d) % n])
+                && (1..=reach).all(|d| angles[i] > angles[(i + n - d) % n])
+        })
+        .collect()
+}
+
+// Moves each corner to the intersection of its incoming and outgoing edges,
+// turning the chamfers produced by marching squares back into sharp corners
+fn sharpen_corners(points: &[Point2<f64>], corners: &[usize]) -> Vec<Point2<f64>> {
+    let n = points.len();
+    let mut result = points.to_vec();
+    if n < 6 {
+        return result;
+    }
+    for &i in corners {
+        let before = points[(i + n - 1) % n];
+        let after = points[(i + 1) % n];
+        let incoming = before - points[(i + n - 3) % n];
+        let outgoing = points[(i + 3) % n] - after;
+        let denominator = incoming.x * outgoing.y - incoming.y * outgoing.x;
+        if denominator.abs() < 1e-9 {
+            continue;
+        }
+        let t = ((after - before).x * outgoing.y - (after - before).y * outgoing.x) / denominator;
+        let corner = before + incoming * t;
+        if (corner - points[i]).magnitude() < 1.5 {
+            result[i] = corner;
+        }
+    }
+    result
+}
+
+// Taubin smoothing (alternating shrink and inflate steps) that keeps corners fixed
+fn smooth(points: &[Point2<f64>], corners: &[usize], passes: usize) -> Vec<Point2<f64>> {
+    let n = points.len();
+    let mut points = points.to_vec();
+    for _ in 0..passes {
+        for factor in [0.5, -0.53] {
+            let previous = points.clone();
+            for i in 0..n {
+                if corners.contains(&i) {
+                    continue;
+                }
+                let average = previous[(i + n - 1) % n]
+                    + (previous[(i + 1) % n] - previous[(i + n - 1) % n]) * 0.5;
+                points[i] = previous[i] + (average - previous[i]) * factor;
+            }
+        }
+    }
+    points
+}
+
+fn append_fitted_contour(
+    path: &mut Path,
+    points: &[Point2<f64>],
+    corners: &[usize],
+    tolerance: f64,
+) {
+    let n = points.len();
+    let tangent_at = |i: usize| (points[(i + 1) % n] - points[(i + n - 1) % n]).normalize();
+
+    // split the closed contour into open runs between corners
+    let mut runs: Vec<(Vec<Point2<f64>>, bool)> = Vec::new();
+    if corners.is_empty() {
+        let mut run = points.to_vec();
+        run.push(points[0]);
+        runs.push((run, false));
+    } else {
+        for (k, &start) in corners.iter().enumerate() {
+            let end = corners[(k + 1) % corners.len()];
+            let length = (end + n - start - 1) % n + 1;
+            runs.push((
+                (0..=length).map(|d| points[(start + d) % n]).collect(),
+                true,
+            ));
+        }
+    }
+
+    path.move_to(runs[0].0[0]);
+    for (run, starts_at_corner) in &runs {
+        let last = run.len() - 1;
+        let segments = if *starts_at_corner {
+            fit_cubics_with_tangents(
+                run,
+                (run[1] - run[0]).normalize(),
+                (run[last - 1] - run[last]).normalize(),
+                tolerance,
+            )
+        } else {
+            // smooth closed contour: the start and end tangents match
+            let tangent = tangent_at(0);
+            fit_cubics_with_tangents(run, tangent, -tangent, tolerance)
+        };
+        for segment in segments {
+            if let Segment::Cubic(_, control1, control2, point) = segment {
+                path.cubic_to(control1, control2, point);
+            }
+        }
+    }
+    path.close();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::path::PathCommand;
+
+    // end points of the commands of each subpath
+    fn subpaths(path: &Path) -> Vec<Vec<Point2<f64>>> {
+        let mut subpaths: Vec<Vec<Point2<f64>>> = Vec::new();
+        for command in path.commands() {
+            match *command {
+                PathCommand::MoveTo(point) => subpaths.push(vec![point]),
+                PathCommand::LineTo(point)
+                | PathCommand::QuadTo(_, point)
+                | PathCommand::CubicTo(_, _, point) => subpaths.last_mut().unwrap().push(point),
+                PathCommand::Close => {}
+            }
+        }
+        subpaths
+    }
+
+    // `size` x `size` bitmap with the values given by `value(x, y)`, zero where it gives none
+    fn bitmap_data(size: usize, value: impl Fn(usize, usize) -> Option<u8>) -> Vec<u8> {
+        (0..size * size)
+            .map(|i| value(i % size, i / size).unwrap_or(0))
+            .collect()
+    }
+
+    #[test]
+    fn filled_square_has_four_corners() {
+        let data = bitmap_data(20, |x, y| {
+            ((5..15).contains(&x) && (5..15).contains(&y)).then_some(255)
+        });
+        let path = trace_bitmap(
+            &Bitmap::new(&data, 20, 20).unwrap(),
+            &TraceOptions::default(),
+        );
+
+        let subpaths = subpaths(&path);
+        assert_eq!(subpaths.len(), 1);
+        // the outline runs along the pixel edges, which sit half way between the samples
+        let mut corners: Vec<_> = subpaths[0]
+            .iter()
+            .map(|point| (point.x.round() as i64, point.y.round() as i64))
+            .collect();
+        corners.sort();
+        corners.dedup();
+        assert_eq!(corners, vec![(5, 5), (5, 15), (15, 5), (15, 15)]);
+        for point in &subpaths[0] {
+            assert!((point.x - point.x.round()).abs() < 0.1);
+            assert!((point.y - point.y.round()).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn ring_hole_has_the_opposite_orientation() {
+        let data = bitmap_data(30, |x, y| {
+            let distance = ((x as f64 - 14.5).powi(2) + (y as f64 - 14.5).powi(2)).sqrt();
+            (5.0..12.0).contains(&distance).then_some(255)
+        });
+        let path = trace_bitmap(
+            &Bitmap::new(&data, 30, 30).unwrap(),
+            &TraceOptions::default(),
+        );
+
+        let mut areas: Vec<f64> = path
+            .to_polygons()
+            .iter()
+            .map(|polygon| signed_area(polygon))
+            .collect();
+        assert_eq!(areas.len(), 2);
+        areas.sort_by(|a, b| a.abs().partial_cmp(&b.abs()).unwrap());
+        let (hole, outer) = (areas[0], areas[1]);
+        assert!(hole * outer < 0.0);
+        assert!((outer.abs() - PI * 12.0 * 12.0).abs() < 0.1 * outer.abs());
+        assert!((hole.abs() - PI * 5.0 * 5.0).abs() < 0.2 * hole.abs());
+    }
+
+    #[test]
+    fn saddle_cells_connect_through_a_bright_center() {
+        // bright top-left and bottom-right quadrants touching at a corner
+        let quadrants = |other: u8| {
+            bitmap_data(8, move |x, y| {
+                Some(if (x < 4) == (y < 4) { 255 } else { other })
+            })
+        };
+        let options = TraceOptions::default();
+
+        // the center of the middle cell is dark: the quadrants are separate
+        let data = quadrants(0);
+        let path = trace_bitmap(&Bitmap::new(&data, 8, 8).unwrap(), &options);
+        assert_eq!(path.to_polygons().len(), 2);
+
+        // the center is bright: one outline around both quadrants
+        let data = quadrants(100);
+        let path = trace_bitmap(&Bitmap::new(&data, 8, 8).unwrap(), &options);
+        assert_eq!(path.to_polygons().len(), 1);
+
+        // inverted, the dark quadrants are connected when the center is dark
+        let options = TraceOptions {
+            invert: true,
+            ..options
+        };
+        let data: Vec<u8> = quadrants(0).iter().map(|&value| value.min(200)).collect();
+        let path = trace_bitmap(&Bitmap::new(&data, 8, 8).unwrap(), &options);
+        assert_eq!(path.to_polygons().len(), 1);
+    }
+
+    #[test]
+    fn rejects_bitmaps_of_the_wrong_size() {
+        let error = Bitmap::new(&[0; 5], 2, 3).unwrap_err();
+        assert_eq!(
+            error,
+            BitmapError::SizeMismatch {
+                expected: 6,
+                actual: 5
+            }
+        );
+        assert_eq!(error.to_string(), "expected 6 bitmap bytes but got 5");
+    }
+}
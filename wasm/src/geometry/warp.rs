@@ -0,0 +1,406 @@
+// Non-affine warping of paths.
+// Curves are re-fitted adaptively in the warped space, so the result follows the warp within a tolerance
+// instead of only moving control points.
+
+use std::fmt;
+
+use cgmath::{point2, vec3, EuclideanSpace, InnerSpace, Matrix3, Point2, SquareMatrix};
+
+use super::{path::Path, rect::Rect, segment::Segment};
+
+const MAX_WARP_DEPTH: u32 = 16;
+
+pub trait Warp {
+    fn warp_point(&self, point: Point2<f64>) -> Point2<f64>;
+}
+
+impl<F: Fn(Point2<f64>) -> Point2<f64>> Warp for F {
+    fn warp_point(&self, point: Point2<f64>) -> Point2<f64> {
+        self(point)
+    }
+}
+
+// Projective transform (homography)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Perspective {
+    pub matrix: Matrix3<f64>,
+}
+
+impl Perspective {
+    // Maps the corners of `rect` (in `Rect::corners` order) to `quad`
+    pub fn from_rect_to_quad(rect: Rect, quad: [Point2<f64>; 4]) -> Option<Perspective> {
+        check_source(rect).ok()?;
+        let rect_to_square = Matrix3::new(
+            1.0 / rect.width(),
+            0.0,
+            0.0,
+            0.0,
+            1.0 / rect.height(),
+            0.0,
+            -rect.min.x / rect.width(),
+            -rect.min.y / rect.height(),
+            1.0,
+        );
+        Some(Perspective {
+            matrix: square_to_quad(quad)? * rect_to_square,
+        })
+    }
+
+    pub fn from_quad_to_quad(from: [Point2<f64>; 4], to: [Point2<f64>; 4]) -> Option<Perspective> {
+        Some(Perspective {
+            matrix: square_to_quad(to)? * square_to_quad(from)?.invert()?,
+        })
+    }
+}
+
+impl Warp for Perspective {
+    fn warp_point(&self, point: Point2<f64>) -> Point2<f64> {
+        let p = self.matrix * vec3(point.x, point.y, 1.0);
+        point2(p.x / p.z, p.y / p.z)
+    }
+}
+
+// Homography mapping the unit square corners (0,0), (1,0), (1,1), (0,1) to the quad
+// (Heckbert, "Fundamentals of Texture Mapping and Image Warping", 1989)
+fn square_to_quad(quad: [Point2<f64>; 4]) -> Option<Matrix3<f64>> {
+    let [p0, p1, p2, p3] = quad;
+    let d1 = p1 - p2;
+    let d2 = p3 - p2;
+    let d3 = p0 - p1 + (p2 - p3);
+    let (g, h) = if d3.x == 0.0 && d3.y == 0.0 {
+        (0.0, 0.0)
+    } else {
+        let det = d1.x * d2.y - d2.x * d1.y;
+        if det == 0.0 {
+            return None;
+        }
+        (
+            (d3.x * d2.y - d2.x * d3.y) / det,
+            (d1.x * d3.y - d3.x * d1.y) / det,
+        )
+    };
+    Some(Matrix3::new(
+        p1.x - p0.x + g * p1.x,
+        p1.y - p0.y + g * p1.y,
+        g,
+        p3.x - p0.x + h * p3.x,
+        p3.y - p0.y + h * p3.y,
+        h,
+        p0.x,
+        p0.y,
+        1.0,
+    ))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WarpError {
+    // source rect without a positive, finite width and height
+    EmptySource,
+}
+
+impl fmt::Display for WarpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarpError::EmptySource => write!(f, "warp source rect has no area"),
+        }
+    }
+}
+
+impl std::error::Error for WarpError {}
+
+// Warps map their source rect onto the unit square, which needs a positive width and height
+fn check_source(source: Rect) -> Result<(), WarpError> {
+    if source.width() > 0.0 && source.height() > 0.0 && source.area().is_finite() {
+        Ok(())
+    } else {
+        Err(WarpError::EmptySource)
+    }
+}
+
+// Bilinear envelope: the source rect is stretched so that its corners land on `corners`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Envelope {
+    source: Rect,
+    // in `Rect::corners` order
+    pub corners: [Point2<f64>; 4],
+}
+
+impl Envelope {
+    pub fn new(source: Rect, corners: [Point2<f64>; 4]) -> Result<Envelope, WarpError> {
+        check_source(source)?;
+        Ok(Envelope { source, corners })
+    }
+
+    // Envelope that leaves the source rect unchanged
+    pub fn identity(source: Rect) -> Result<Envelope, WarpError> {
+        Envelope::new(source, source.corners())
+    }
+
+    pub fn source(&self) -> Rect {
+        self.source
+    }
+}
+
+impl Warp for Envelope {
+    fn warp_point(&self, point: Point2<f64>) -> Point2<f64> {
+        let u = (point.x - self.source.min.x) / self.source.width();
+        let v = (point.y - self.source.min.y) / self.source.height();
+        let [c0, c1, c2, c3] = self.corners.map(|c| c.to_vec());
+        Point2::from_vec(
+            c0 * ((1.0 - u) * (1.0 - v))
+                + c1 * (u * (1.0 - v))
+                + c2 * (u * v)
+                + c3 * ((1.0 - u) * v),
+        )
+    }
+}
+
+// Bicubic Bézier patch warp: the source rect is mapped onto the patch
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeshWarp {
+    source: Rect,
+    // control_points[row][column], rows along y and columns along x
+    pub control_points: [[Point2<f64>; 4]; 4],
+}
+
+impl MeshWarp {
+    pub fn new(source: Rect, control_points: [[Point2<f64>; 4]; 4]) -> Result<MeshWarp, WarpError> {
+        check_source(source)?;
+        Ok(MeshWarp {
+            source,
+            control_points,
+        })
+    }
+
+    // Patch that leaves the source rect unchanged, as a starting point for editing
+    pub fn identity(source: Rect) -> Result<MeshWarp, WarpError> {
+        let mut control_points = [[source.min; 4]; 4];
+        for (row, points) in control_points.iter_mut().enumerate() {
+            for (column, point) in points.iter_mut().enumerate() {
+                *point = point2(
+                    source.min.x + source.width() * column as f64 / 3.0,
+                    source.min.y + source.height() * row as f64 / 3.0,
+                );
+            }
+        }
+        MeshWarp::new(source, control_points)
+    }
+
+    pub fn source(&self) -> Rect {
+        self.source
+    }
+}
+
+impl Warp for MeshWarp {
+    fn warp_point(&self, point: Point2<f64>) -> Point2<f64> {
+        let bernstein = |t: f64| {
+            let mt = 1.0 - t;
+            [mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t]
+        };
+        let bu = bernstein((point.x - self.source.min.x) / self.source.width());
+        let bv = bernstein((point.y - self.source.min.y) / self.source.height());
+        let mut result = point2(0.0, 0.0).to_vec();
+        for (row, points) in self.control_points.iter().enumerate() {
+            for (column, control) in points.iter().enumerate() {
+                result += control.to_vec() * (bu[column] * bv[row]);
+            }
+        }
+        Point2::from_vec(result)
+    }
+}
+
+impl Path {
+    // Maps the path through `warp`, subdividing until the result is within `tolerance` of the warped curves
+    pub fn warp(&self, warp: &impl Warp, tolerance: f64) -> Path {
+        let mut path = Path::new();
+        for subpath in self.subpaths() {
+            path.move_to(warp.warp_point(subpath.start()));
+            for segment in &subpath.segments {
+                warp_segment(&mut path, segment, warp, tolerance, 0.0, 1.0, 0);
+            }
+            if subpath.closed {
+                // the implicit closing line bends like any other line
+                if subpath.end() != subpath.start() {
+                    let closing = Segment::Line(subpath.end(), subpath.start());
+                    warp_segment(&mut path, &closing, warp, tolerance, 0.0, 1.0, 0);
+                }
+                path.close();
+            }
+        }
+        path
+    }
+}
+
+fn warp_segment(
+    path: &mut Path,
+    segment: &Segment,
+    warp: &impl Warp,
+    tolerance: f64,
+    t0: f64,
+    t1: f64,
+    depth: u32,
+) {
+    let warped = |t: f64| warp.warp_point(segment.point_at(t));
+    let start = warped(t0);
+    let end = warped(t1);
+    let samples = [0.25, 0.5, 0.75].map(|s| warped(t0 + (t1 - t0) * s));
+
+    // a line that stays straight (e.g. under perspective) is kept as a line
+    if let Segment::Line(..) = segment {
+        let chord = end - start;
+        let length = chord.magnitude();
+        let is_straight = samples.iter().all(|sample| {
+            let offset = *sample - start;
+            let distance = if length == 0.0 {
+                offset.magnitude()
+            } else {
+                (chord.x * offset.y - chord.y * offset.x).abs() / length
+            };
+            distance <= tolerance
+        });
+        if is_straight || depth >= MAX_WARP_DEPTH {
+            path.line_to(end);
+            return;
+        }
+    }
+
+    // cubic matching the warped end points and tangents (derivatives by central differences)
+    let h = 1e-6;
+    let derivative = |t: f64| (warped(t + h) - warped(t - h)) / (2.0 * h);
+    let scale = (t1 - t0) / 3.0;
+    let cubic = Segment::Cubic(
+        start,
+        start + derivative(t0) * scale,
+        end - derivative(t1) * scale,
+        end,
+    );
+    let error = [0.25, 0.5, 0.75]
+        .iter()
+        .zip(samples)
+        .map(|(s, sample)| (cubic.point_at(*s) - sample).magnitude())
+        .fold(0.0, f64::max);
+
+    if error <= tolerance || depth >= MAX_WARP_DEPTH {
+        if let Segment::Cubic(_, control1, control2, point) = cubic {
+            path.cubic_to(control1, control2, point);
+        }
+        return;
+    }
+
+    let middle = (t0 + t1) / 2.0;
+    warp_segment(path, segment, warp, tolerance, t0, middle, depth + 1);
+    warp_segment(path, segment, warp, tolerance, middle, t1, depth + 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::path::PathCommand;
+
+    fn assert_close(a: Point2<f64>, b: Point2<f64>) {
+        assert!((a - b).magnitude() < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    fn unit_square() -> Rect {
+        Rect::new(point2(0.0, 0.0), point2(1.0, 1.0))
+    }
+
+    const QUAD: [Point2<f64>; 4] = [
+        Point2::new(1.0, 2.0),
+        Point2::new(5.0, 1.0),
+        Point2::new(6.0, 7.0),
+        Point2::new(0.0, 4.0),
+    ];
+
+    #[test]
+    fn homography_maps_the_unit_square_onto_the_quad_and_back() {
+        let forward = Perspective::from_rect_to_quad(unit_square(), QUAD).unwrap();
+        for (corner, expected) in unit_square().corners().into_iter().zip(QUAD) {
+            assert_close(forward.warp_point(corner), expected);
+        }
+
+        let backward = Perspective::from_quad_to_quad(QUAD, unit_square().corners()).unwrap();
+        for point in [point2(0.5, 0.5), point2(0.1, 0.9), point2(0.3, 0.2)] {
+            assert_close(backward.warp_point(forward.warp_point(point)), point);
+        }
+
+        // straight lines stay straight under perspective
+        let path = Path::from_svg("M0 0 L1 1").unwrap();
+        let warped = path.warp(&forward, 0.01);
+        assert_eq!(warped.to_segments().len(), 1);
+    }
+
+    #[test]
+    fn identity_warps_leave_paths_unchanged() {
+        let source = Rect::new(point2(-2.0, 1.0), point2(4.0, 3.0));
+        let path = Path::from_svg("M-1 1.5 C0 3 2 1 3 2.5 L1 2 Z").unwrap();
+        let envelope = Envelope::identity(source).unwrap();
+        let mesh = MeshWarp::identity(source).unwrap();
+        for warped in [path.warp(&envelope, 1e-3), path.warp(&mesh, 1e-3)] {
+            let segments = warped.to_segments();
+            assert_eq!(segments.len(), 3);
+            for (segment, original) in segments.iter().zip(path.to_segments()) {
+                for t in [0.0, 0.25, 0.5, 0.75, 1.0] {
+                    assert_close(segment.point_at(t), original.point_at(t));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn envelope_interpolates_the_corners_bilinearly() {
+        let envelope = Envelope::new(unit_square(), QUAD).unwrap();
+        assert_close(envelope.warp_point(point2(1.0, 0.0)), QUAD[1]);
+        assert_close(
+            envelope.warp_point(point2(0.5, 0.5)),
+            Point2::centroid(&QUAD),
+        );
+    }
+
+    #[test]
+    fn rejects_empty_source_rects() {
+        for source in [
+            Rect::new(point2(0.0, 0.0), point2(0.0, 1.0)),
+            Rect::new(point2(0.0, 1.0), point2(1.0, 0.0)),
+            Rect::new(point2(0.0, 0.0), point2(f64::INFINITY, 1.0)),
+        ] {
+            assert_eq!(Envelope::identity(source), Err(WarpError::EmptySource));
+            assert_eq!(MeshWarp::identity(source), Err(WarpError::EmptySource));
+            assert_eq!(Perspective::from_rect_to_quad(source, QUAD), None);
+        }
+        assert_eq!(
+            WarpError::EmptySource.to_string(),
+            "warp source rect has no area"
+        );
+    }
+
+    #[test]
+    fn warps_the_implicit_closing_line() {
+        let path = Path::from_commands(vec![
+            PathCommand::MoveTo(point2(0.0, 0.0)),
+            PathCommand::LineTo(point2(10.0, 0.0)),
+            PathCommand::LineTo(point2(10.0, 10.0)),
+            PathCommand::Close,
+        ])
+        .unwrap();
+        let source = Rect::new(point2(0.0, 0.0), point2(10.0, 10.0));
+        let mut corners = source.corners();
+        corners[2] = point2(10.0, 20.0);
+        let envelope = Envelope::new(source, corners).unwrap();
+        let tolerance = 0.01;
+        let warped = path.warp(&envelope, tolerance);
+
+        // the diagonal from (10, 10) back to the start bends through the warped midpoint
+        let midpoint = envelope.warp_point(point2(5.0, 5.0));
+        assert_close(midpoint, point2(5.0, 7.5));
+        let distance = warped
+            .subpaths()
+            .iter()
+            .flat_map(|subpath| subpath.segments.clone())
+            .flat_map(|segment| (0..=100).map(move |i| segment.point_at(i as f64 / 100.0)))
+            .map(|point| (point - midpoint).magnitude())
+            .fold(f64::INFINITY, f64::min);
+        assert!(distance < 2.0 * tolerance, "{}", distance);
+        assert_eq!(warped.commands().last(), Some(&PathCommand::Close));
+    }
+}
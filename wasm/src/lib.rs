@@ -1,10 +1,21 @@
-mod geometry;
-mod renderer;
+#[cfg(feature = "wasm")]
+mod bindings;
+pub mod geometry;
+pub mod raster;
+pub mod renderer;
 mod util;
 
+// shared by the examples and integration tests, not part of the API
+#[doc(hidden)]
+#[cfg(not(target_arch = "wasm32"))]
+pub use util::block_on;
+
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
+#[cfg(feature = "wasm")]
 use wasm_bindgen_console_logger::DEFAULT_LOGGER;
 
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
 pub fn start() {
     std::panic::set_hook(Box::new(console_error_panic_hook::hook));
@@ -0,0 +1,6 @@
+mod blend;
+mod coverage;
+mod pixmap;
+
+pub use blend::{blend, composite};
+pub use pixmap::Pixmap;
@@ -0,0 +1,152 @@
+// Blend modes on the CPU, following the same formulas as `fs_blend` in the shader
+
+use cgmath::{vec3, vec4, Vector3, Vector4};
+
+use crate::renderer::BlendMode;
+
+// Composites a premultiplied source with coverage over a premultiplied destination:
+// the source color is first mixed with the backdrop by the blend mode, then drawn with source-over.
+pub fn composite(
+    color: Vector4<f32>,
+    coverage: f32,
+    destination: Vector4<f32>,
+    blend_mode: BlendMode,
+) -> Vector4<f32> {
+    let source_alpha = color.w * coverage;
+    let backdrop_alpha = destination.w;
+    let mixed = if blend_mode == BlendMode::Normal {
+        color.truncate()
+    } else {
+        let source = unpremultiply(color);
+        let backdrop = unpremultiply(destination);
+        (source * (1.0 - backdrop_alpha) + blend(blend_mode, backdrop, source) * backdrop_alpha)
+            * color.w
+    };
+    let rgb = mixed * coverage + destination.truncate() * (1.0 - source_alpha);
+    vec4(
+        rgb.x,
+        rgb.y,
+        rgb.z,
+        source_alpha + backdrop_alpha * (1.0 - source_alpha),
+    )
+}
+
+// The blended color B(Cb, Cs) of straight backdrop and source colors
+pub fn blend(mode: BlendMode, backdrop: Vector3<f32>, source: Vector3<f32>) -> Vector3<f32> {
+    let separable = |f: fn(f32, f32) -> f32| {
+        vec3(
+            f(backdrop.x, source.x),
+            f(backdrop.y, source.y),
+            f(backdrop.z, source.z),
+        )
+    };
+    match mode {
+        BlendMode::Normal => source,
+        BlendMode::Multiply => separable(|b, s| b * s),
+        BlendMode::Screen => separable(screen),
+        BlendMode::Overlay => separable(|b, s| hard_light(s, b)),
+        BlendMode::Darken => separable(f32::min),
+        BlendMode::Lighten => separable(f32::max),
+        BlendMode::ColorDodge => separable(|b, s| {
+            if b == 0.0 {
+                0.0
+            } else if s >= 1.0 {
+                1.0
+            } else {
+                (b / (1.0 - s)).min(1.0)
+            }
+        }),
+        BlendMode::ColorBurn => separable(|b, s| {
+            if b >= 1.0 {
+                1.0
+            } else if s <= 0.0 {
+                0.0
+            } else {
+                1.0 - ((1.0 - b) / s).min(1.0)
+            }
+        }),
+        BlendMode::HardLight => separable(hard_light),
+        BlendMode::SoftLight => separable(|b, s| {
+            if s <= 0.5 {
+                b - (1.0 - 2.0 * s) * b * (1.0 - b)
+            } else {
+                let d = if b <= 0.25 {
+                    ((16.0 * b - 12.0) * b + 4.0) * b
+                } else {
+                    b.sqrt()
+                };
+                b + (2.0 * s - 1.0) * (d - b)
+            }
+        }),
+        BlendMode::Difference => separable(|b, s| (b - s).abs()),
+        BlendMode::Exclusion => separable(|b, s| b + s - 2.0 * b * s),
+        BlendMode::Hue => set_luminosity(
+            set_saturation(source, saturation(backdrop)),
+            luminosity(backdrop),
+        ),
+        BlendMode::Saturation => set_luminosity(
+            set_saturation(backdrop, saturation(source)),
+            luminosity(backdrop),
+        ),
+        BlendMode::Color => set_luminosity(source, luminosity(backdrop)),
+        BlendMode::Luminosity => set_luminosity(backdrop, luminosity(source)),
+    }
+}
+
+fn unpremultiply(color: Vector4<f32>) -> Vector3<f32> {
+    if color.w > 0.0 {
+        color.truncate() / color.w
+    } else {
+        vec3(0.0, 0.0, 0.0)
+    }
+}
+
+fn screen(backdrop: f32, source: f32) -> f32 {
+    backdrop + source - backdrop * source
+}
+
+fn hard_light(backdrop: f32, source: f32) -> f32 {
+    if source <= 0.5 {
+        backdrop * 2.0 * source
+    } else {
+        screen(backdrop, 2.0 * source - 1.0)
+    }
+}
+
+fn luminosity(color: Vector3<f32>) -> f32 {
+    0.3 * color.x + 0.59 * color.y + 0.11 * color.z
+}
+
+fn saturation(color: Vector3<f32>) -> f32 {
+    color.x.max(color.y).max(color.z) - color.x.min(color.y).min(color.z)
+}
+
+fn set_luminosity(color: Vector3<f32>, target: f32) -> Vector3<f32> {
+    let d = target - luminosity(color);
+    clip_color(color + vec3(d, d, d))
+}
+
+// Brings the channels back into [0, 1] while keeping the luminosity
+fn clip_color(color: Vector3<f32>) -> Vector3<f32> {
+    let l = luminosity(color);
+    let min = color.x.min(color.y).min(color.z);
+    let max = color.x.max(color.y).max(color.z);
+    let mut color = color;
+    if min < 0.0 {
+        color = color.map(|c| l + (c - l) * l / (l - min));
+    }
+    if max > 1.0 {
+        color = color.map(|c| l + (c - l) * (1.0 - l) / (max - l));
+    }
+    color
+}
+
+fn set_saturation(color: Vector3<f32>, target: f32) -> Vector3<f32> {
+    let min = color.x.min(color.y).min(color.z);
+    let max = color.x.max(color.y).max(color.z);
+    if max > min {
+        color.map(|c| (c - min) * target / (max - min))
+    } else {
+        vec3(0.0, 0.0, 0.0)
+    }
+}
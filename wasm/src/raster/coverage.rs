@@ -0,0 +1,283 @@
+// Exact area coverage of filled polygons, computed scanline by scanline.
+// Each pixel row is cut into horizontal strips at edge endpoints and crossings, so inside every strip
+// the edges keep their order and the filled parts are trapezoids whose area per pixel is integrated exactly.
+
+use cgmath::Point2;
+
+use crate::geometry::FillRule;
+
+#[derive(Clone, Copy, Debug)]
+struct Edge {
+    top: Point2<f64>,
+    bottom: Point2<f64>,
+    // +1 for edges running towards +y, -1 otherwise
+    winding: i32,
+}
+
+impl Edge {
+    fn x_at(&self, y: f64) -> f64 {
+        self.top.x + (self.bottom.x - self.top.x) * (y - self.top.y) / (self.bottom.y - self.top.y)
+    }
+}
+
+// Coverage in [0, 1] of each pixel of a width x height grid, where pixel (x, y) spans [x, x + 1] x [y, y + 1]
+pub fn coverage(
+    polygons: &[Vec<Point2<f64>>],
+    fill_rule: FillRule,
+    width: usize,
+    height: usize,
+) -> Vec<f32> {
+    let mut coverage = vec![0.0f32; width * height];
+
+    let mut edges: Vec<Edge> = Vec::new();
+    for polygon in polygons {
+        for i in 0..polygon.len() {
+            let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+            if !(a.x.is_finite() && a.y.is_finite() && b.x.is_finite() && b.y.is_finite()) {
+                continue;
+            }
+            if a.y < b.y {
+                edges.push(Edge {
+                    top: a,
+                    bottom: b,
+                    winding: 1,
+                });
+            } else if a.y > b.y {
+                edges.push(Edge {
+                    top: b,
+                    bottom: a,
+                    winding: -1,
+                });
+            }
+        }
+    }
+    edges.sort_by(|a, b| a.top.y.total_cmp(&b.top.y));
+
+    let mut first_pending = 0;
+    let mut active: Vec<Edge> = Vec::new();
+    for row in 0..height {
+        let (row_top, row_bottom) = (row as f64, row as f64 + 1.0);
+        active.retain(|edge| edge.bottom.y > row_top);
+        while first_pending < edges.len() && edges[first_pending].top.y < row_bottom {
+            if edges[first_pending].bottom.y > row_top {
+                active.push(edges[first_pending]);
+            }
+            first_pending += 1;
+        }
+        if active.is_empty() {
+            continue;
+        }
+
+        // strip boundaries: the row's edges, edge endpoints and crossings inside the row
+        let mut cuts = vec![row_top, row_bottom];
+        for (i, edge) in active.iter().enumerate() {
+            for y in [edge.top.y, edge.bottom.y] {
+                if y > row_top && y < row_bottom {
+                    cuts.push(y);
+                }
+            }
+            for other in &active[i + 1..] {
+                if let Some(y) = crossing(edge, other) {
+                    if y > row_top && y < row_bottom {
+                        cuts.push(y);
+                    }
+                }
+            }
+        }
+        cuts.sort_by(f64::total_cmp);
+        cuts.dedup();
+
+        let pixels = &mut coverage[row * width..(row + 1) * width];
+        for strip in cuts.windows(2) {
+            let (y0, y1) = (strip[0], strip[1]);
+            if y1 - y0 <= 1e-12 {
+                continue;
+            }
+            let mut crossings: Vec<(f64, f64, i32)> = active
+                .iter()
+                .filter(|edge| edge.top.y <= y0 && edge.bottom.y >= y1)
+                .map(|edge| (edge.x_at(y0), edge.x_at(y1), edge.winding))
+                .collect();
+            crossings.sort_by(|a, b| (a.0 + a.1).total_cmp(&(b.0 + b.1)));
+
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].2;
+                if fill_rule.is_inside(winding) {
+                    add_trapezoid(
+                        pixels,
+                        (pair[0].0, pair[0].1),
+                        (pair[1].0, pair[1].1),
+                        y1 - y0,
+                    );
+                }
+            }
+        }
+    }
+
+    for value in &mut coverage {
+        *value = value.clamp(0.0, 1.0);
+    }
+    coverage
+}
+
+// Height at which two edges cross, if they aren't parallel
+fn crossing(a: &Edge, b: &Edge) -> Option<f64> {
+    let slope_a = (a.bottom.x - a.top.x) / (a.bottom.y - a.top.y);
+    let slope_b = (b.bottom.x - b.top.x) / (b.bottom.y - b.top.y);
+    if slope_a == slope_b {
+        return None;
+    }
+    // a.top.x + (y - a.top.y) * slope_a = b.top.x + (y - b.top.y) * slope_b
+    let y = (b.top.x - a.top.x + a.top.y * slope_a - b.top.y * slope_b) / (slope_a - slope_b);
+    y.is_finite().then_some(y)
+}
+
+// Adds the area between the left and right edges (given by their x at the top and bottom of a strip of `height`)
+fn add_trapezoid(pixels: &mut [f32], left: (f64, f64), right: (f64, f64), height: f64) {
+    let width = pixels.len() as f64;
+    let start = left.0.min(left.1).floor().clamp(0.0, width) as usize;
+    let end = right.0.max(right.1).ceil().clamp(0.0, width) as usize;
+    // columns between these are fully covered by the strip
+    let full_start = left.0.max(left.1).ceil();
+    let full_end = right.0.min(right.1).floor();
+    for (column, pixel) in pixels.iter_mut().enumerate().take(end).skip(start) {
+        let x = column as f64;
+        let area = if x >= full_start && x + 1.0 <= full_end {
+            height
+        } else {
+            height * (average_clamped(right, x, x + 1.0) - average_clamped(left, x, x + 1.0))
+        };
+        *pixel += area as f32;
+    }
+}
+
+// Average over a strip of x clamped to [low, high], where x varies linearly from `x.0` to `x.1`
+fn average_clamped(x: (f64, f64), low: f64, high: f64) -> f64 {
+    let at = |s: f64| (x.0 + (x.1 - x.0) * s).clamp(low, high);
+    if x.0 == x.1 {
+        return at(0.0);
+    }
+    // the clamped value is linear between the points where x crosses the bounds
+    let mut breaks = vec![0.0, 1.0];
+    for bound in [low, high] {
+        let s = (bound - x.0) / (x.1 - x.0);
+        if s > 0.0 && s < 1.0 {
+            breaks.push(s);
+        }
+    }
+    breaks.sort_by(f64::total_cmp);
+    breaks
+        .windows(2)
+        .map(|piece| (piece[1] - piece[0]) * (at(piece[0]) + at(piece[1])) / 2.0)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::point2;
+
+    use super::*;
+
+    fn shoelace(polygon: &[Point2<f64>]) -> f64 {
+        let n = polygon.len();
+        (0..n)
+            .map(|i| {
+                let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+                a.x * b.y - b.x * a.y
+            })
+            .sum::<f64>()
+            / 2.0
+    }
+
+    fn total(pixels: &[f32]) -> f64 {
+        pixels.iter().map(|&c| c as f64).sum()
+    }
+
+    fn rect(x0: f64, y0: f64, x1: f64, y1: f64) -> Vec<Point2<f64>> {
+        vec![
+            point2(x0, y0),
+            point2(x1, y0),
+            point2(x1, y1),
+            point2(x0, y1),
+        ]
+    }
+
+    #[test]
+    fn pixel_aligned_rect_is_fully_covered() {
+        let pixels = coverage(&[rect(1.0, 1.0, 3.0, 4.0)], FillRule::NonZero, 5, 5);
+        for y in 0..5 {
+            for x in 0..5 {
+                let inside = (1..3).contains(&x) && (1..4).contains(&y);
+                assert_eq!(pixels[y * 5 + x], if inside { 1.0 } else { 0.0 });
+            }
+        }
+    }
+
+    #[test]
+    fn partial_pixels_get_their_covered_area() {
+        let pixels = coverage(&[rect(0.5, 0.5, 2.5, 2.5)], FillRule::NonZero, 3, 3);
+        assert_eq!(
+            pixels,
+            vec![0.25, 0.5, 0.25, 0.5, 1.0, 0.5, 0.25, 0.5, 0.25]
+        );
+
+        // a diagonal edge halves the pixels it crosses
+        let triangle = vec![point2(0.0, 0.0), point2(2.0, 0.0), point2(2.0, 2.0)];
+        let pixels = coverage(&[triangle], FillRule::NonZero, 2, 2);
+        assert_eq!(pixels, vec![0.5, 1.0, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn coverage_sums_to_the_polygon_area() {
+        for polygon in [
+            vec![point2(0.3, 0.2), point2(7.7, 1.1), point2(3.2, 6.9)],
+            // concave, with edges that start and end inside pixels
+            vec![
+                point2(1.1, 1.3),
+                point2(8.6, 0.4),
+                point2(5.2, 4.45),
+                point2(9.3, 8.8),
+                point2(0.7, 7.1),
+            ],
+        ] {
+            for fill_rule in [FillRule::NonZero, FillRule::EvenOdd] {
+                let pixels = coverage(std::slice::from_ref(&polygon), fill_rule, 10, 10);
+                assert!((total(&pixels) - shoelace(&polygon).abs()).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn crossing_edges_split_strips() {
+        // bowtie: two triangles meeting at (2, 2), the crossing is inside a pixel row
+        let bowtie = vec![
+            point2(0.0, 0.5),
+            point2(4.0, 3.5),
+            point2(4.0, 0.5),
+            point2(0.0, 3.5),
+        ];
+        let pixels = coverage(&[bowtie], FillRule::NonZero, 4, 4);
+        assert!((total(&pixels) - 6.0).abs() < 1e-5);
+        assert!(pixels.iter().all(|&c| (0.0..=1.0).contains(&c)));
+    }
+
+    #[test]
+    fn fill_rules_differ_on_overlaps() {
+        let polygons = [rect(0.0, 0.0, 4.0, 4.0), rect(2.0, 2.0, 6.0, 6.0)];
+        // union
+        let nonzero = coverage(&polygons, FillRule::NonZero, 8, 8);
+        assert!((total(&nonzero) - 28.0).abs() < 1e-6);
+        // the overlap is a hole
+        let even_odd = coverage(&polygons, FillRule::EvenOdd, 8, 8);
+        assert!((total(&even_odd) - 24.0).abs() < 1e-6);
+        assert_eq!(even_odd[3 * 8 + 3], 0.0);
+    }
+
+    #[test]
+    fn geometry_outside_the_grid_is_clipped() {
+        let pixels = coverage(&[rect(-2.0, -2.0, 1.5, 10.0)], FillRule::NonZero, 3, 3);
+        assert_eq!(total(&pixels), 4.5);
+        assert_eq!(&pixels[..3], &[1.0, 0.5, 0.0]);
+    }
+}
@@ -0,0 +1,130 @@
+use cgmath::{vec4, Point2, Vector4};
+
+use super::{blend::composite, coverage::coverage};
+use crate::{
+    geometry::{FillRule, Path},
+    renderer::{BlendMode, Scene},
+    util::premultiply,
+};
+
+// RGBA image with premultiplied alpha like the GPU render targets, drawn with anti-aliased
+// source-over compositing after blending. Colors passed in are straight (non-premultiplied) RGBA.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pixmap {
+    width: usize,
+    height: usize,
+    pixels: Vec<Vector4<f32>>,
+}
+
+impl Pixmap {
+    // Transparent black image
+    pub fn new(width: usize, height: usize) -> Pixmap {
+        Pixmap {
+            width,
+            height,
+            pixels: vec![vec4(0.0, 0.0, 0.0, 0.0); width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn clear(&mut self, color: Vector4<f32>) {
+        self.pixels.fill(premultiply(color));
+    }
+
+    // Premultiplied RGBA
+    pub fn pixel(&self, x: usize, y: usize) -> Vector4<f32> {
+        self.pixels[y * self.width + x]
+    }
+
+    // Fills the path given in pixel coordinates (y down, pixel (x, y) spans [x, x + 1] x [y, y + 1])
+    pub fn fill_path(&mut self, path: &Path, fill_rule: FillRule, color: Vector4<f32>) {
+        self.fill_path_blended(path, fill_rule, color, BlendMode::Normal);
+    }
+
+    // Like `fill_path`, mixing the color with the pixels beneath it by the blend mode
+    pub fn fill_path_blended(
+        &mut self,
+        path: &Path,
+        fill_rule: FillRule,
+        color: Vector4<f32>,
+        blend_mode: BlendMode,
+    ) {
+        self.fill_polygons(
+            &path.to_polygons(),
+            fill_rule,
+            premultiply(color),
+            blend_mode,
+        );
+    }
+
+    // Draws the scene like `Renderer::render`: clear, then each shape transformed by the view projection
+    // and its own transform. Nothing is culled, like in the render pipeline.
+    pub fn render_scene(&mut self, scene: &Scene) {
+        self.clear(scene.clear_color);
+
+        let (width, height) = (self.width as f64, self.height as f64);
+        for (_, shape) in scene.shapes() {
+            let uniforms = shape.uniforms(scene.view_proj);
+            let to_pixels = |position: Point2<f32>| {
+                let clip = uniforms.view_proj * vec4(position.x, position.y, 0.0, 1.0);
+                let (x, y) = ((clip.x / clip.w) as f64, (clip.y / clip.w) as f64);
+                Point2::new((x + 1.0) / 2.0 * width, (1.0 - y) / 2.0 * height)
+            };
+
+            let vertices = &shape.geometry.vertices;
+            let triangles: Vec<Vec<Point2<f64>>> = shape
+                .geometry
+                .indices
+                .chunks_exact(3)
+                .map(|triangle| {
+                    triangle
+                        .iter()
+                        .map(|&index| to_pixels(vertices[index as usize].position))
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+            // the triangles don't overlap, so their union is the tessellated shape
+            // (the uniforms' color is already premultiplied)
+            self.fill_polygons(
+                &triangles,
+                FillRule::NonZero,
+                uniforms.color,
+                shape.blend_mode,
+            );
+        }
+    }
+
+    // `color` is premultiplied
+    fn fill_polygons(
+        &mut self,
+        polygons: &[Vec<Point2<f64>>],
+        fill_rule: FillRule,
+        color: Vector4<f32>,
+        blend_mode: BlendMode,
+    ) {
+        let coverage = coverage(polygons, fill_rule, self.width, self.height);
+        for (pixel, coverage) in self.pixels.iter_mut().zip(coverage) {
+            if coverage > 0.0 {
+                *pixel = composite(color, coverage, *pixel, blend_mode);
+            }
+        }
+    }
+
+    // Rows of 8-bit premultiplied RGBA, top row first, like `Renderer::read_pixels`
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|pixel| {
+                [pixel.x, pixel.y, pixel.z, pixel.w]
+                    .map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8)
+            })
+            .collect()
+    }
+}
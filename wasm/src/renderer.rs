@@ -1,4 +1,21 @@
+mod blend_mode;
+mod camera;
+// demo content from the original prototype
+#[allow(dead_code, unused_variables, unused_mut, clippy::needless_return)]
 mod example_mesh;
 mod mesh;
+#[allow(clippy::module_inception)]
 mod renderer;
+mod scene;
+mod surface;
+mod tessellation;
 mod uniforms;
+
+pub use blend_mode::BlendMode;
+pub use camera::Camera;
+pub use mesh::Vertex;
+pub use renderer::Renderer;
+pub use scene::{Scene, Shape, ShapeId};
+pub use surface::{SurfaceSource, Window};
+pub use tessellation::tessellate_fill;
+pub use uniforms::UniformsValue;
@@ -0,0 +1,35 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// How a shape's color mixes with the colors beneath it before source-over compositing
+// (https://www.w3.org/TR/compositing-1/#blending). The values are shared with the shader.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u32)]
+pub enum BlendMode {
+    #[default]
+    Normal = 0,
+    Multiply = 1,
+    Screen = 2,
+    Overlay = 3,
+    Darken = 4,
+    Lighten = 5,
+    ColorDodge = 6,
+    ColorBurn = 7,
+    HardLight = 8,
+    SoftLight = 9,
+    Difference = 10,
+    Exclusion = 11,
+    Hue = 12,
+    Saturation = 13,
+    Color = 14,
+    Luminosity = 15,
+}
+
+impl BlendMode {
+    // Modes other than normal need to read the backdrop, which fixed-function blending can't
+    pub fn reads_backdrop(&self) -> bool {
+        *self != BlendMode::Normal
+    }
+}
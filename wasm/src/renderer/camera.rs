@@ -0,0 +1,193 @@
+use cgmath::{point2, vec2, vec3, Matrix4, Point2, Rad, Vector2};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use super::uniforms::UniformsValue;
+
+// Limits of `zoom_at`, so that scrolling can't collapse or blow up the scene
+const MIN_ZOOM: f64 = 0.01;
+const MAX_ZOOM: f64 = 100.0;
+
+// Pan, zoom and rotation of the scene in a viewport.
+// A world point p is drawn at the screen point (x, y) + zoom * rotate(p), in CSS pixels (y down),
+// so positive rotations turn the scene clockwise on screen.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Camera {
+    x: f64,
+    y: f64,
+    zoom: f64,
+    // radians
+    rotation: f64,
+    // viewport size in CSS pixels
+    width: f64,
+    height: f64,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Camera {
+    // Shows world coordinates as CSS pixels
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(width: f64, height: f64) -> Camera {
+        Camera {
+            x: 0.0,
+            y: 0.0,
+            zoom: 1.0,
+            rotation: 0.0,
+            width,
+            height,
+        }
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn x(&self) -> f64 {
+        self.x
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn y(&self) -> f64 {
+        self.y
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn zoom(&self) -> f64 {
+        self.zoom
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn rotation(&self) -> f64 {
+        self.rotation
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = setTransform))]
+    pub fn set_transform(&mut self, x: f64, y: f64, zoom: f64, rotation: f64) {
+        self.x = x;
+        self.y = y;
+        self.zoom = zoom;
+        self.rotation = rotation;
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = setViewport))]
+    pub fn set_viewport(&mut self, width: f64, height: f64) {
+        self.width = width;
+        self.height = height;
+    }
+
+    // Moves the scene by a distance in screen pixels, e.g. while dragging
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        self.x += dx;
+        self.y += dy;
+    }
+
+    // Scales the zoom by `factor`, keeping the world point under the screen point in place (e.g. the cursor)
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = zoomAt))]
+    pub fn zoom_at(&mut self, screen_x: f64, screen_y: f64, factor: f64) {
+        let screen = point2(screen_x, screen_y);
+        let world = self.screen_to_world(screen);
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.keep_in_place(world, screen);
+    }
+
+    // Rotates the scene by `angle` radians around a screen point
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = rotateAt))]
+    pub fn rotate_at(&mut self, screen_x: f64, screen_y: f64, angle: f64) {
+        let screen = point2(screen_x, screen_y);
+        let world = self.screen_to_world(screen);
+        self.rotation += angle;
+        self.keep_in_place(world, screen);
+    }
+}
+
+impl Camera {
+    pub fn world_to_screen(&self, point: Point2<f64>) -> Point2<f64> {
+        point2(self.x, self.y) + self.rotate(vec2(point.x, point.y)) * self.zoom
+    }
+
+    pub fn screen_to_world(&self, point: Point2<f64>) -> Point2<f64> {
+        let (sin, cos) = self.rotation.sin_cos();
+        let v = (point - point2(self.x, self.y)) / self.zoom;
+        point2(cos * v.x + sin * v.y, -sin * v.x + cos * v.y)
+    }
+
+    // Maps world coordinates to CSS pixels
+    pub fn view(&self) -> Matrix4<f32> {
+        Matrix4::from_translation(vec3(self.x as f32, self.y as f32, 0.0))
+            * Matrix4::from_angle_z(Rad(self.rotation as f32))
+            * Matrix4::from_scale(self.zoom as f32)
+    }
+
+    // Maps world coordinates to clip space, for `UniformsValue::view_proj`
+    pub fn view_proj(&self) -> Matrix4<f32> {
+        UniformsValue::pixel_projection(self.width as f32, self.height as f32) * self.view()
+    }
+
+    fn rotate(&self, v: Vector2<f64>) -> Vector2<f64> {
+        let (sin, cos) = self.rotation.sin_cos();
+        vec2(cos * v.x - sin * v.y, sin * v.x + cos * v.y)
+    }
+
+    // Moves the camera so that the world point is drawn at the screen point
+    fn keep_in_place(&mut self, world: Point2<f64>, screen: Point2<f64>) {
+        let offset = screen - self.rotate(vec2(world.x, world.y)) * self.zoom;
+        self.x = offset.x;
+        self.y = offset.y;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: Point2<f64>, expected: Point2<f64>) {
+        assert!(
+            (actual.x - expected.x).abs() < 1e-9 && (actual.y - expected.y).abs() < 1e-9,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn screen_to_world_inverts_world_to_screen() {
+        let mut camera = Camera::new(800.0, 600.0);
+        camera.set_transform(120.0, -40.0, 2.5, 0.7);
+        for point in [point2(0.0, 0.0), point2(10.0, -3.0), point2(-250.5, 1e3)] {
+            assert_close(camera.screen_to_world(camera.world_to_screen(point)), point);
+            assert_close(camera.world_to_screen(camera.screen_to_world(point)), point);
+        }
+        // a quarter turn clockwise on screen
+        camera.set_transform(0.0, 0.0, 1.0, std::f64::consts::FRAC_PI_2);
+        assert_close(camera.world_to_screen(point2(1.0, 0.0)), point2(0.0, 1.0));
+    }
+
+    #[test]
+    fn zoom_at_keeps_the_point_under_the_cursor() {
+        let mut camera = Camera::new(800.0, 600.0);
+        camera.set_transform(30.0, 20.0, 1.5, -0.3);
+        let cursor = point2(412.0, 187.0);
+        let world = camera.screen_to_world(cursor);
+
+        camera.zoom_at(cursor.x, cursor.y, 1.25);
+        assert_eq!(camera.zoom(), 1.875);
+        assert_close(camera.world_to_screen(world), cursor);
+
+        // clamped zooms keep the point in place too
+        camera.zoom_at(cursor.x, cursor.y, 1e6);
+        assert_eq!(camera.zoom(), MAX_ZOOM);
+        assert_close(camera.world_to_screen(world), cursor);
+        camera.zoom_at(cursor.x, cursor.y, 1e-9);
+        assert_eq!(camera.zoom(), MIN_ZOOM);
+        assert_close(camera.world_to_screen(world), cursor);
+    }
+}
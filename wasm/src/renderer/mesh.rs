@@ -1,5 +1,5 @@
 use crate::util::cast_to_u8_slice;
-use cgmath::{Point2, Point3};
+use cgmath::Point2;
 use lyon::lyon_tessellation::VertexBuffers;
 use wgpu::util::DeviceExt;
 
@@ -39,6 +39,7 @@ impl Mesh {
         Mesh::new(device, &geometry.vertices, &geometry.indices)
     }
 
+    #[allow(dead_code)]
     pub fn pentagon(device: &wgpu::Device) -> Mesh {
         const VERTICES: &[Vertex] = &[
             Vertex {
@@ -60,9 +61,10 @@ impl Mesh {
 
         const INDICES: &[u16] = &[0, 1, 4, 1, 2, 4, 2, 3, 4];
 
-        Self::new(&device, VERTICES, INDICES)
+        Self::new(device, VERTICES, INDICES)
     }
 
+    #[allow(dead_code)]
     pub fn triangle(device: &wgpu::Device) -> Mesh {
         const VERTICES: &[Vertex] = &[
             Vertex {
@@ -78,7 +80,7 @@ impl Mesh {
 
         const INDICES: &[u16] = &[0, 1, 2];
 
-        Self::new(&device, VERTICES, INDICES)
+        Self::new(device, VERTICES, INDICES)
     }
 
     pub fn buffer_layout() -> wgpu::VertexBufferLayout<'static> {
@@ -1,60 +1,277 @@
-use std::iter;
+use std::{collections::HashMap, iter, num::NonZeroU32, sync::mpsc};
 
-use crate::renderer::{example_mesh::example_text, mesh::Mesh, uniforms::UniformsValue};
+use cgmath::{point2, vec3, vec4, Matrix3, Matrix4, SquareMatrix, Vector4};
+use lyon::lyon_tessellation::VertexBuffers;
 
-use super::uniforms::Uniforms;
+use crate::geometry::Rect;
+use crate::renderer::{
+    blend_mode::BlendMode,
+    camera::Camera,
+    mesh::{Mesh, Vertex},
+    scene::{Scene, Shape, ShapeId},
+    tessellation::tessellate_fill,
+};
+use crate::util::premultiply;
+
+use super::{
+    surface::SurfaceSource,
+    uniforms::{Uniforms, UniformsValue},
+};
 use log::info;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
-#[wasm_bindgen]
+// Where frames are drawn: a canvas or window surface, or an offscreen texture whose pixels can be read back
+enum RenderTarget {
+    Surface {
+        surface: wgpu::Surface,
+        config: wgpu::SurfaceConfiguration,
+    },
+    Texture {
+        texture: wgpu::Texture,
+        format: wgpu::TextureFormat,
+    },
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub struct Renderer {
+    // size of the render target in physical pixels
     width: u32,
     height: u32,
-    surface: wgpu::Surface,
+    device_pixel_ratio: f64,
+    target: RenderTarget,
+    format: wgpu::TextureFormat,
     device: wgpu::Device,
     queue: wgpu::Queue,
-    config: wgpu::SurfaceConfiguration,
     pipeline: wgpu::RenderPipeline,
+    // draws shapes whose blend mode reads the backdrop
+    blend_pipeline: wgpu::RenderPipeline,
+    backdrop_bind_group_layout: wgpu::BindGroupLayout,
+    // resolved copies of the framebuffer for blend modes, used in turns
+    // because a pass can't sample the texture it resolves into
+    backdrops: [Backdrop; 2],
     multisampled_framebuffer: wgpu::TextureView,
-    mesh: Mesh,
+    scene: Scene,
+    // pan, zoom and rotation of the scene in CSS pixels
+    camera: Camera,
+    // GPU buffers of the scene's shapes, except for empty ones
+    meshes: HashMap<ShapeId, Mesh>,
+    // the rect, in shape coordinates, that a path shape's mesh was clipped to
+    clips: HashMap<ShapeId, Rect>,
     uniforms: Uniforms,
+    // set when shapes or the view changed since the uniforms were last uploaded
+    uniforms_outdated: bool,
 }
 
+#[allow(non_upper_case_globals)]
 const sample_count: u32 = 4;
 
+struct Backdrop {
+    view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+}
+
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
 impl Renderer {
     #[wasm_bindgen]
     pub async fn new(canvas: web_sys::HtmlCanvasElement) -> Renderer {
-        info!("renderer");
+        Renderer::with_surface(&canvas).await
+    }
+}
 
-        let width = canvas.width();
-        let height = canvas.height();
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Renderer {
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
+    pub fn render(&mut self) {
+        // the surface texture has to stay alive until it's presented
+        let (output, view) = match &self.target {
+            RenderTarget::Surface { surface, .. } => {
+                let output = surface.get_current_texture().unwrap();
+                let view = output
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                (Some(output), view)
+            }
+            RenderTarget::Texture { texture, .. } => (
+                None,
+                texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            ),
+        };
 
-        let instance = wgpu::Instance::new(wgpu::Backends::all());
-        let surface = instance.create_surface_from_canvas(&canvas);
+        if self.uniforms_outdated {
+            self.upload_uniforms();
+        }
 
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
+        let clear_color = premultiply(self.scene.clear_color);
+        let clear_color = wgpu::Color {
+            r: clear_color.x as f64,
+            g: clear_color.y as f64,
+            b: clear_color.z as f64,
+            a: clear_color.w as f64,
+        };
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        // Blend modes other than normal need what's beneath the shape, so the pass ends before
+        // each such shape and resolves into a backdrop texture, which the next pass samples
+        let shapes: Vec<(ShapeId, BlendMode)> = self
+            .scene
+            .shapes()
+            .map(|(id, shape)| (id, shape.blend_mode))
+            .collect();
+        let mut starts: Vec<usize> = shapes
+            .iter()
+            .enumerate()
+            .filter(|(_, (id, blend_mode))| {
+                blend_mode.reads_backdrop() && self.meshes.contains_key(id)
             })
-            .await
-            .unwrap();
+            .map(|(index, _)| index)
+            .collect();
+        starts.insert(0, 0);
+        let ends = starts[1..].iter().copied().chain([shapes.len()]);
 
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    features: wgpu::Features::empty(),
-                    limits: wgpu::Limits::downlevel_webgl2_defaults(),
-                    label: None,
-                },
-                None, // Trace path
+        for (pass, (start, end)) in starts.iter().copied().zip(ends).enumerate() {
+            let resolve_target = if pass == starts.len() - 1 {
+                &view
+            } else {
+                &self.backdrops[pass % 2].view
+            };
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.multisampled_framebuffer,
+                    resolve_target: Some(resolve_target),
+                    ops: wgpu::Operations {
+                        load: if pass == 0 {
+                            wgpu::LoadOp::Clear(clear_color)
+                        } else {
+                            wgpu::LoadOp::Load
+                        },
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            for (index, (id, blend_mode)) in shapes.iter().enumerate().take(end).skip(start) {
+                let Some(mesh) = self.meshes.get(id) else {
+                    continue;
+                };
+                if blend_mode.reads_backdrop() {
+                    render_pass.set_pipeline(&self.blend_pipeline);
+                    // resolved by the previous pass
+                    render_pass.set_bind_group(1, &self.backdrops[(pass + 1) % 2].bind_group, &[]);
+                } else {
+                    render_pass.set_pipeline(&self.pipeline);
+                }
+                render_pass.set_bind_group(
+                    0,
+                    &self.uniforms.bind_group,
+                    &[self.uniforms.offset(index)],
+                );
+                render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                render_pass
+                    .set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                render_pass.draw_indexed(0..mesh.num_indices, 0, 0..1);
+            }
+        }
+
+        self.queue.submit(iter::once(encoder.finish()));
+        if let Some(output) = output {
+            output.present();
+        }
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = clearShapes))]
+    pub fn clear_shapes(&mut self) {
+        self.scene.clear();
+        self.meshes.clear();
+        self.clips.clear();
+        self.uniforms_outdated = true;
+    }
+
+    // Pans, zooms (around the origin of the scene) and rotates (in radians) the whole scene.
+    // A scene point p is drawn at CSS pixel (x, y) + zoom * rotate(p).
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = setViewTransform))]
+    pub fn set_view_transform(&mut self, x: f32, y: f32, zoom: f32, rotation: f32) {
+        let mut camera = self.camera;
+        camera.set_transform(x as f64, y as f64, zoom as f64, rotation as f64);
+        self.set_camera(&camera);
+    }
+
+    // A copy of the camera; changes to it take effect with `set_camera`
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn camera(&self) -> Camera {
+        self.camera
+    }
+
+    // Applies the camera's pan, zoom and rotation, keeping the renderer's viewport size
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = setCamera))]
+    pub fn set_camera(&mut self, camera: &Camera) {
+        self.camera
+            .set_transform(camera.x(), camera.y(), camera.zoom(), camera.rotation());
+        self.update_view_proj();
+        // every shape's uniforms depend on the view
+        self.upload_uniforms();
+    }
+
+    // Resizes the render target to the CSS size of the canvas times the device pixel ratio,
+    // while shapes keep being laid out in CSS pixels
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
+    pub fn resize(&mut self, width: u32, height: u32, device_pixel_ratio: f64) {
+        let max_size = self.device.limits().max_texture_dimension_2d;
+        let physical_size =
+            |size: u32| ((size as f64 * device_pixel_ratio).round() as u32).clamp(1, max_size);
+        self.width = physical_size(width);
+        self.height = physical_size(height);
+        self.device_pixel_ratio = device_pixel_ratio;
+
+        match &mut self.target {
+            RenderTarget::Surface { surface, config } => {
+                config.width = self.width;
+                config.height = self.height;
+                surface.configure(&self.device, config);
+            }
+            RenderTarget::Texture { texture, format } => {
+                *texture = create_offscreen_texture(&self.device, *format, self.width, self.height);
+            }
+        }
+        self.multisampled_framebuffer =
+            create_multisampled_framebuffer(&self.device, self.format, self.width, self.height);
+        self.backdrops = [0, 1].map(|_| {
+            create_backdrop(
+                &self.device,
+                &self.backdrop_bind_group_layout,
+                self.format,
+                self.width,
+                self.height,
             )
-            .await
-            .unwrap();
+        });
 
+        self.camera.set_viewport(width as f64, height as f64);
+        self.update_view_proj();
+    }
+}
+
+impl Renderer {
+    // Renders to a browser canvas or a native window
+    pub async fn with_surface(source: &impl SurfaceSource) -> Renderer {
+        info!("renderer");
+
+        let (width, height) = source.size();
+
+        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        let surface = source.create_surface(&instance);
+
+        let adapter = request_adapter(&instance, Some(&surface)).await;
+        let (device, queue) = request_device(&adapter).await;
+
+        // wgpu 0.13 has no surface alpha mode; WebGL canvases composite as premultiplied by default
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             format: surface.get_supported_formats(&adapter)[0],
@@ -64,30 +281,269 @@ impl Renderer {
         };
         surface.configure(&device, &config);
 
+        let format = config.format;
+        Renderer::with_target(
+            device,
+            queue,
+            RenderTarget::Surface { surface, config },
+            format,
+            width,
+            height,
+            Scene::example(width, height),
+        )
+    }
+
+    // Renders to an offscreen texture instead of a canvas, e.g. in tests or for thumbnails.
+    // `format` has to be one of the 8-bit RGBA or BGRA formats.
+    pub async fn new_headless(width: u32, height: u32, format: wgpu::TextureFormat) -> Renderer {
+        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        let adapter = request_adapter(&instance, None).await;
+        Renderer::new_headless_with_adapter(&adapter, width, height, format).await
+    }
+
+    async fn new_headless_with_adapter(
+        adapter: &wgpu::Adapter,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> Renderer {
+        assert!(
+            matches!(
+                format,
+                wgpu::TextureFormat::Rgba8Unorm
+                    | wgpu::TextureFormat::Rgba8UnormSrgb
+                    | wgpu::TextureFormat::Bgra8Unorm
+                    | wgpu::TextureFormat::Bgra8UnormSrgb
+            ),
+            "unsupported headless format {:?}",
+            format
+        );
+        let (device, queue) = request_device(adapter).await;
+
+        let texture = create_offscreen_texture(&device, format, width, height);
+
+        // no demo content offscreen: an empty scene on a white background
+        let scene = Scene::new(
+            vec4(1.0, 1.0, 1.0, 1.0),
+            UniformsValue::pixel_projection(width as f32, height as f32),
+        );
+        Renderer::with_target(
+            device,
+            queue,
+            RenderTarget::Texture { texture, format },
+            format,
+            width,
+            height,
+            scene,
+        )
+    }
+
+    pub fn device_pixel_ratio(&self) -> f64 {
+        self.device_pixel_ratio
+    }
+
+    pub fn scene(&self) -> &Scene {
+        &self.scene
+    }
+
+    // Changes the fill color of a shape without touching its geometry.
+    // Returns false if there is no shape with this id.
+    pub fn set_fill_color(&mut self, id: ShapeId, color: Vector4<f32>) -> bool {
+        let Some(index) = self.scene.index(id) else {
+            return false;
+        };
+        let view_proj = self.scene.view_proj;
+        let shape = self.scene.shape_mut(id).unwrap();
+        shape.color = color;
+        if !self.uniforms_outdated {
+            self.uniforms
+                .write_at(&self.queue, index, shape.uniforms(view_proj));
+        }
+        true
+    }
+
+    // Changes how a shape mixes with the shapes beneath it.
+    // Returns false if there is no shape with this id.
+    pub fn set_blend_mode(&mut self, id: ShapeId, blend_mode: BlendMode) -> bool {
+        let Some(index) = self.scene.index(id) else {
+            return false;
+        };
+        let view_proj = self.scene.view_proj;
+        let shape = self.scene.shape_mut(id).unwrap();
+        shape.blend_mode = blend_mode;
+        if !self.uniforms_outdated {
+            self.uniforms
+                .write_at(&self.queue, index, shape.uniforms(view_proj));
+        }
+        true
+    }
+
+    // Adds the shape on top of the others; it's drawn from the next `render`
+    pub fn add_shape(&mut self, shape: Shape) -> ShapeId {
+        let id = self.scene.add_shape(shape);
+        self.update_mesh(id);
+        self.uniforms_outdated = true;
+        id
+    }
+
+    // Returns false if there is no shape with this id.
+    pub fn remove_shape(&mut self, id: ShapeId) -> bool {
+        if self.scene.remove_shape(id).is_none() {
+            return false;
+        }
+        self.meshes.remove(&id);
+        self.clips.remove(&id);
+        self.uniforms_outdated = true;
+        true
+    }
+
+    // Replaces the geometry, color and transform of the shape.
+    // Returns false if there is no shape with this id.
+    pub fn update_shape(&mut self, id: ShapeId, shape: Shape) -> bool {
+        if !self.scene.update_shape(id, shape) {
+            return false;
+        }
+        self.update_mesh(id);
+        self.uniforms_outdated = true;
+        true
+    }
+
+    // Copies the last rendered frame into rows of premultiplied RGBA bytes, top row first.
+    // Returns None when rendering to a canvas surface.
+    pub async fn read_pixels(&self) -> Option<Vec<u8>> {
+        let RenderTarget::Texture { texture, format } = &self.target else {
+            return None;
+        };
+
+        // rows of the copy have to be aligned in the buffer
+        let row_size = self.width * 4;
+        let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row_size = row_size.div_ceil(alignment) * alignment;
+
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback Buffer"),
+            size: (padded_row_size * self.height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Readback Encoder"),
+            });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(padded_row_size),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.queue.submit(iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        loop {
+            self.device.poll(wgpu::Maintain::Wait);
+            match receiver.try_recv() {
+                Ok(result) => break result.unwrap(),
+                Err(mpsc::TryRecvError::Empty) => yield_now().await,
+                Err(mpsc::TryRecvError::Disconnected) => panic!("buffer mapping was dropped"),
+            }
+        }
+
+        let mut pixels = Vec::with_capacity((row_size * self.height) as usize);
+        {
+            let data = slice.get_mapped_range();
+            for row in data.chunks(padded_row_size as usize) {
+                pixels.extend_from_slice(&row[..row_size as usize]);
+            }
+        }
+        buffer.unmap();
+
+        if matches!(
+            format,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+        ) {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+        Some(pixels)
+    }
+
+    fn update_view_proj(&mut self) {
+        self.scene.view_proj = self.camera.view_proj();
+        self.uniforms_outdated = true;
+
+        // path shapes whose visible part moved out of what they were clipped to
+        let outdated: Vec<ShapeId> = self
+            .scene
+            .shapes()
+            .filter(|(id, shape)| {
+                self.clips.get(id).is_some_and(|clip| {
+                    visible_rect(self.scene.view_proj * shape.transform)
+                        .is_none_or(|visible| !clip.contains_rect(&visible))
+                })
+            })
+            .map(|(id, _)| id)
+            .collect();
+        for id in outdated {
+            self.update_mesh(id);
+        }
+    }
+
+    // Builds the GPU buffers of the shape, clipped to the viewport if it has a path
+    fn update_mesh(&mut self, id: ShapeId) {
+        let shape = self.scene.shape(id).unwrap();
+        let (mesh, clip) = create_visible_mesh(&self.device, shape, self.scene.view_proj);
+        match mesh {
+            Some(mesh) => self.meshes.insert(id, mesh),
+            None => self.meshes.remove(&id),
+        };
+        match clip {
+            Some(clip) => self.clips.insert(id, clip),
+            None => self.clips.remove(&id),
+        };
+    }
+
+    fn upload_uniforms(&mut self) {
+        let values: Vec<UniformsValue> = self
+            .scene
+            .shapes()
+            .map(|(_, shape)| shape.uniforms(self.scene.view_proj))
+            .collect();
+        self.uniforms.write(&self.device, &self.queue, &values);
+        self.uniforms_outdated = false;
+    }
+
+    fn with_target(
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        target: RenderTarget,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+        scene: Scene,
+    ) -> Renderer {
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Shader"),
             source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
         });
 
-        let uniforms = Uniforms::new(
-            &device,
-            UniformsValue {
-                view_proj: [
-                    [2.0 / width as f32, 0.0, 0.0, 0.0],
-                    [0.0, -2.0 / height as f32, 0.0, 0.0],
-                    [0.0, 0.0, 1.0, 0.0],
-                    [-1.0, 1.0, 0.0, 1.0],
-                ]
-                .into(),
-                // tailwind blue-900
-                color: cgmath::vec4(
-                    0x1e as f32 / 0xff as f32,
-                    0x3a as f32 / 0xff as f32,
-                    0x8a as f32 / 0xff as f32,
-                    1.0,
-                ),
-            },
-        );
+        let uniforms = Uniforms::new(&device);
 
         let render_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -95,129 +551,356 @@ impl Renderer {
                 bind_group_layouts: &[&uniforms.bind_group_layout],
                 push_constant_ranges: &[],
             });
+        let pipeline =
+            create_pipeline(&device, &render_pipeline_layout, &shader, "fs_main", format);
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[Mesh::buffer_layout()],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent::REPLACE,
-                        alpha: wgpu::BlendComponent::REPLACE,
-                    }),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                // Setting this to anything other than Fill requires Features::POLYGON_MODE_LINE
-                // or Features::POLYGON_MODE_POINT
-                polygon_mode: wgpu::PolygonMode::Fill,
-                // Requires Features::DEPTH_CLIP_CONTROL
-                unclipped_depth: false,
-                // Requires Features::CONSERVATIVE_RASTERIZATION
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: sample_count,
-                ..Default::default()
-            },
-            // If the pipeline will be used with a multiview render pass, this
-            // indicates how many array layers the attachments will have.
-            multiview: None,
-        });
-
-        let mesh = Mesh::from_tessellation(
-            &device,
-            &example_text("Hello, world!", 64.0, 48.0, 32.0, 96.0),
-        );
-
-        let multisampled_texture_extent = wgpu::Extent3d {
-            width,
-            height,
-            depth_or_array_layers: 1,
-        };
-        let multisampled_frame_descriptor = &wgpu::TextureDescriptor {
-            size: multisampled_texture_extent,
-            mip_level_count: 1,
-            sample_count,
-            dimension: wgpu::TextureDimension::D2,
-            format: config.format,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            label: None,
-        };
+        let backdrop_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Backdrop Bind Group Layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                }],
+            });
+        let blend_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Blend Pipeline Layout"),
+                bind_group_layouts: &[&uniforms.bind_group_layout, &backdrop_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let blend_pipeline =
+            create_pipeline(&device, &blend_pipeline_layout, &shader, "fs_blend", format);
+        let backdrops = [0, 1]
+            .map(|_| create_backdrop(&device, &backdrop_bind_group_layout, format, width, height));
 
-        let multisampled_framebuffer = device
-            .create_texture(multisampled_frame_descriptor)
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        let multisampled_framebuffer =
+            create_multisampled_framebuffer(&device, format, width, height);
 
-        Self {
+        let ids: Vec<ShapeId> = scene.shapes().map(|(id, _)| id).collect();
+        let mut renderer = Self {
             width,
             height,
-            surface,
+            device_pixel_ratio: 1.0,
+            target,
+            format,
             device,
             queue,
-            config,
             pipeline,
+            blend_pipeline,
+            backdrop_bind_group_layout,
+            backdrops,
             multisampled_framebuffer,
-            mesh,
+            scene,
+            camera: Camera::new(width as f64, height as f64),
+            meshes: HashMap::new(),
+            clips: HashMap::new(),
             uniforms,
+            uniforms_outdated: true,
+        };
+        for id in ids {
+            renderer.update_mesh(id);
         }
+        renderer
     }
+}
 
-    #[wasm_bindgen]
-    pub fn render(&mut self) {
-        let output = self.surface.get_current_texture().unwrap();
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+fn create_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    fragment_entry_point: &str,
+    format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Render Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[Mesh::buffer_layout()],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: fragment_entry_point,
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                // source-over with premultiplied colors
+                blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            // mirrored transforms flip the winding, and a fill has no back to hide
+            cull_mode: None,
+            // Setting this to anything other than Fill requires Features::POLYGON_MODE_LINE
+            // or Features::POLYGON_MODE_POINT
+            polygon_mode: wgpu::PolygonMode::Fill,
+            // Requires Features::DEPTH_CLIP_CONTROL
+            unclipped_depth: false,
+            // Requires Features::CONSERVATIVE_RASTERIZATION
+            conservative: false,
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            ..Default::default()
+        },
+        // If the pipeline will be used with a multiview render pass, this
+        // indicates how many array layers the attachments will have.
+        multiview: None,
+    })
+}
 
-        let mut encoder = self
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Render Encoder"),
-            });
+// None for shapes without triangles, which wgpu can't draw from empty buffers
+fn create_mesh(device: &wgpu::Device, geometry: &VertexBuffers<Vertex, u16>) -> Option<Mesh> {
+    if geometry.indices.is_empty() {
+        return None;
+    }
+    Some(Mesh::from_tessellation(device, geometry))
+}
 
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &self.multisampled_framebuffer,
-                    resolve_target: Some(&view),
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 1.0,
-                            g: 1.0,
-                            b: 1.0,
-                            a: 1.0,
-                        }),
-                        store: true,
-                    },
-                })],
-                depth_stencil_attachment: None,
-            });
+// Shapes with a path are tessellated only inside the visible area plus a margin, so what's
+// off-screen costs no vertices and small pans can reuse the mesh.
+// Returns the mesh and the rect it was clipped to, if anything was clipped.
+fn create_visible_mesh(
+    device: &wgpu::Device,
+    shape: &Shape,
+    view_proj: Matrix4<f32>,
+) -> (Option<Mesh>, Option<Rect>) {
+    let (Some((path, fill_rule)), Some(visible)) =
+        (&shape.path, visible_rect(view_proj * shape.transform))
+    else {
+        return (create_mesh(device, &shape.geometry), None);
+    };
+    let clip = visible.inflate(visible.width().max(visible.height()) / 2.0);
+    if path
+        .bounds()
+        .is_none_or(|bounds| clip.contains_rect(&bounds))
+    {
+        // nothing to cut away, and nothing to redo when the view changes
+        return (create_mesh(device, &shape.geometry), None);
+    }
+    match tessellate_fill(&path.clip_to_rect(&clip), *fill_rule) {
+        Ok(geometry) => (create_mesh(device, &geometry), Some(clip)),
+        Err(_) => (create_mesh(device, &shape.geometry), None),
+    }
+}
+
+// The bounds, in the coordinates `to_clip` maps from, of what lands in the viewport.
+// None if the plane z = 0 isn't mapped onto the whole viewport.
+fn visible_rect(to_clip: Matrix4<f32>) -> Option<Rect> {
+    let m = to_clip.cast::<f64>()?;
+    // the x, y and w rows, for points with z = 0
+    let from_clip = Matrix3::new(
+        m.x.x, m.x.y, m.x.w, m.y.x, m.y.y, m.y.w, m.w.x, m.w.y, m.w.w,
+    )
+    .invert()?;
+    let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+        .map(|(x, y)| from_clip * vec3(x, y, 1.0));
+    if corners.iter().any(|corner| corner.z <= 0.0) {
+        return None;
+    }
+    Rect::from_points(corners.map(|corner| point2(corner.x / corner.z, corner.y / corner.z)))
+}
+
+fn create_offscreen_texture(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        label: Some("Offscreen Texture"),
+    })
+}
+
+fn create_multisampled_framebuffer(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+) -> wgpu::TextureView {
+    let multisampled_texture_extent = wgpu::Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    };
+    let multisampled_frame_descriptor = &wgpu::TextureDescriptor {
+        size: multisampled_texture_extent,
+        mip_level_count: 1,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        label: None,
+    };
 
-            render_pass.set_pipeline(&self.pipeline);
-            render_pass.set_bind_group(0, &self.uniforms.bind_group, &[]);
-            render_pass.set_vertex_buffer(0, self.mesh.vertex_buffer.slice(..));
-            render_pass
-                .set_index_buffer(self.mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-            render_pass.draw_indexed(0..self.mesh.num_indices, 0, 0..1);
+    device
+        .create_texture(multisampled_frame_descriptor)
+        .create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+// A texture the framebuffer can be resolved into and then sampled by the blend pipeline
+fn create_backdrop(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+) -> Backdrop {
+    let view = device
+        .create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            label: Some("Backdrop Texture"),
+        })
+        .create_view(&wgpu::TextureViewDescriptor::default());
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Backdrop Bind Group"),
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::TextureView(&view),
+        }],
+    });
+    Backdrop { view, bind_group }
+}
+
+// Renders the scene offscreen and returns its RGBA pixels, e.g. for thumbnails
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub async fn render_thumbnail(width: u32, height: u32) -> Vec<u8> {
+    // WebGL needs a canvas for its context even when drawing offscreen
+    let canvas = web_sys::OffscreenCanvas::new(width, height).unwrap();
+    let instance = wgpu::Instance::new(wgpu::Backends::all());
+    let surface = instance.create_surface_from_offscreen_canvas(&canvas);
+    let adapter = request_adapter(&instance, Some(&surface)).await;
+
+    // blends in the same space as the tests and `Pixmap`
+    let mut renderer = Renderer::new_headless_with_adapter(
+        &adapter,
+        width,
+        height,
+        wgpu::TextureFormat::Rgba8Unorm,
+    )
+    .await;
+    for (_, shape) in Scene::example(width, height).shapes() {
+        renderer.add_shape(shape.clone());
+    }
+    renderer.render();
+    renderer.read_pixels().await.unwrap()
+}
+
+async fn request_adapter(
+    instance: &wgpu::Instance,
+    compatible_surface: Option<&wgpu::Surface>,
+) -> wgpu::Adapter {
+    // fall back to a software adapter on machines without a GPU (e.g. CI)
+    for force_fallback_adapter in [false, true] {
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface,
+                force_fallback_adapter,
+            })
+            .await;
+        if let Some(adapter) = adapter {
+            return adapter;
         }
+    }
+    panic!("no graphics adapter available");
+}
 
-        self.queue.submit(iter::once(encoder.finish()));
-        output.present();
+async fn request_device(adapter: &wgpu::Adapter) -> (wgpu::Device, wgpu::Queue) {
+    adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                features: wgpu::Features::empty(),
+                limits: wgpu::Limits::downlevel_webgl2_defaults(),
+                label: None,
+            },
+            None, // Trace path
+        )
+        .await
+        .unwrap()
+}
+
+// Lets the browser run its event loop while waiting for the GPU
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+async fn yield_now() {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        web_sys::window()
+            .unwrap()
+            .set_timeout_with_callback(&resolve)
+            .unwrap();
+    });
+    wasm_bindgen_futures::JsFuture::from(promise).await.unwrap();
+}
+
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+async fn yield_now() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_rect_close(actual: Rect, expected: Rect) {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-4;
+        assert!(
+            close(actual.min.x, expected.min.x)
+                && close(actual.min.y, expected.min.y)
+                && close(actual.max.x, expected.max.x)
+                && close(actual.max.y, expected.max.y),
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn visible_rect_is_the_viewport_in_shape_coordinates() {
+        let view_proj = UniformsValue::pixel_projection(800.0, 600.0);
+        assert_rect_close(
+            visible_rect(view_proj).unwrap(),
+            Rect::new(point2(0.0, 0.0), point2(800.0, 600.0)),
+        );
+
+        let mut camera = Camera::new(800.0, 600.0);
+        camera.set_transform(-100.0, 50.0, 2.0, 0.0);
+        let transform = Matrix4::from_translation(vec3(10.0, 0.0, 0.0));
+        assert_rect_close(
+            visible_rect(camera.view_proj() * transform).unwrap(),
+            Rect::new(point2(40.0, -25.0), point2(440.0, 275.0)),
+        );
+
+        // a shape scaled to nothing covers no area
+        assert!(visible_rect(view_proj * Matrix4::from_scale(0.0)).is_none());
     }
 }
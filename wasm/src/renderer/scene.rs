@@ -0,0 +1,147 @@
+use cgmath::{Matrix4, SquareMatrix, Vector4};
+use lyon::lyon_tessellation::VertexBuffers;
+
+use super::{
+    blend_mode::BlendMode, example_mesh::example_text, mesh::Vertex, uniforms::UniformsValue,
+};
+use crate::{
+    geometry::{FillRule, Path},
+    util::premultiply,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShapeId(pub u32);
+
+// A tessellated shape filled with a single color
+#[derive(Clone, Debug)]
+pub struct Shape {
+    pub geometry: VertexBuffers<Vertex, u16>,
+    // straight (non-premultiplied) RGBA
+    pub color: Vector4<f32>,
+    // maps the geometry into scene coordinates
+    pub transform: Matrix4<f32>,
+    // how the color mixes with the shapes beneath it
+    pub blend_mode: BlendMode,
+    // what the geometry was tessellated from, if anything, so the renderer
+    // can tessellate just the part near the viewport
+    pub path: Option<(Path, FillRule)>,
+}
+
+impl Shape {
+    pub fn new(geometry: VertexBuffers<Vertex, u16>, color: Vector4<f32>) -> Shape {
+        Shape {
+            geometry,
+            color,
+            transform: Matrix4::identity(),
+            blend_mode: BlendMode::Normal,
+            path: None,
+        }
+    }
+
+    pub fn uniforms(&self, view_proj: Matrix4<f32>) -> UniformsValue {
+        UniformsValue {
+            view_proj: view_proj * self.transform,
+            color: premultiply(self.color),
+            blend_mode: self.blend_mode as u32,
+        }
+    }
+}
+
+// Everything the renderer draws in a frame, independent of any GPU resources
+#[derive(Clone, Debug)]
+pub struct Scene {
+    // straight (non-premultiplied) RGBA
+    pub clear_color: Vector4<f32>,
+    // maps scene coordinates to clip space
+    pub view_proj: Matrix4<f32>,
+    // in drawing order, back to front
+    shapes: Vec<(ShapeId, Shape)>,
+    next_id: u32,
+}
+
+impl Scene {
+    pub fn new(clear_color: Vector4<f32>, view_proj: Matrix4<f32>) -> Scene {
+        Scene {
+            clear_color,
+            view_proj,
+            shapes: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    // The demo scene: text in pixel coordinates (y down) on a white background
+    pub fn example(width: u32, height: u32) -> Scene {
+        let mut scene = Scene::new(
+            cgmath::vec4(1.0, 1.0, 1.0, 1.0),
+            UniformsValue::pixel_projection(width as f32, height as f32),
+        );
+        scene.add_shape(Shape::new(
+            example_text("Hello, world!", 64.0, 48.0, 32.0, 96.0),
+            // tailwind blue-900
+            cgmath::vec4(
+                0x1e as f32 / 0xff as f32,
+                0x3a as f32 / 0xff as f32,
+                0x8a as f32 / 0xff as f32,
+                1.0,
+            ),
+        ));
+        scene
+    }
+
+    // Adds the shape on top of the others
+    pub fn add_shape(&mut self, shape: Shape) -> ShapeId {
+        let id = ShapeId(self.next_id);
+        self.next_id += 1;
+        self.shapes.push((id, shape));
+        id
+    }
+
+    pub fn remove_shape(&mut self, id: ShapeId) -> Option<Shape> {
+        let index = self.index(id)?;
+        Some(self.shapes.remove(index).1)
+    }
+
+    // Replaces the shape, keeping its place in the drawing order.
+    // Returns false if there is no shape with this id.
+    pub fn update_shape(&mut self, id: ShapeId, shape: Shape) -> bool {
+        match self.shape_mut(id) {
+            Some(existing) => {
+                *existing = shape;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn shape(&self, id: ShapeId) -> Option<&Shape> {
+        self.index(id).map(|index| &self.shapes[index].1)
+    }
+
+    pub fn shape_mut(&mut self, id: ShapeId) -> Option<&mut Shape> {
+        let index = self.index(id)?;
+        Some(&mut self.shapes[index].1)
+    }
+
+    // Shapes in drawing order
+    pub fn shapes(&self) -> impl Iterator<Item = (ShapeId, &Shape)> {
+        self.shapes.iter().map(|(id, shape)| (*id, shape))
+    }
+
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.shapes.clear();
+    }
+
+    // Position of the shape in the drawing order
+    pub fn index(&self, id: ShapeId) -> Option<usize> {
+        // ids are handed out in increasing order and shapes keep their order
+        self.shapes.binary_search_by_key(&id, |(id, _)| *id).ok()
+    }
+}
@@ -1,6 +1,9 @@
 struct Uniforms {
     view_proj: mat4x4<f32>,
+    // premultiplied alpha, like everything the pipeline blends
     color: vec4<f32>,
+    // `BlendMode`, only read by fs_blend
+    blend_mode: u32,
 };
 
 @group(0) @binding(0)
@@ -27,3 +30,124 @@ fn vs_main(
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     return uniforms.color;
 }
+
+// What was drawn beneath the shape, resolved from the multisampled framebuffer (premultiplied)
+@group(1) @binding(0)
+var backdrop: texture_2d<f32>;
+
+// Separable and non-separable blend modes from https://www.w3.org/TR/compositing-1/#blending,
+// matching `raster::blend`
+
+fn screen(cb: vec3<f32>, cs: vec3<f32>) -> vec3<f32> {
+    return cb + cs - cb * cs;
+}
+
+fn hard_light(cb: vec3<f32>, cs: vec3<f32>) -> vec3<f32> {
+    return select(screen(cb, 2.0 * cs - 1.0), cb * 2.0 * cs, cs <= vec3<f32>(0.5));
+}
+
+fn color_dodge(cb: vec3<f32>, cs: vec3<f32>) -> vec3<f32> {
+    let dodged = min(vec3<f32>(1.0), cb / max(1.0 - cs, vec3<f32>(1e-6)));
+    return select(select(dodged, vec3<f32>(1.0), cs >= vec3<f32>(1.0)), vec3<f32>(0.0), cb == vec3<f32>(0.0));
+}
+
+fn color_burn(cb: vec3<f32>, cs: vec3<f32>) -> vec3<f32> {
+    let burned = 1.0 - min(vec3<f32>(1.0), (1.0 - cb) / max(cs, vec3<f32>(1e-6)));
+    return select(select(burned, vec3<f32>(0.0), cs <= vec3<f32>(0.0)), vec3<f32>(1.0), cb >= vec3<f32>(1.0));
+}
+
+fn soft_light(cb: vec3<f32>, cs: vec3<f32>) -> vec3<f32> {
+    let d = select(sqrt(cb), ((16.0 * cb - 12.0) * cb + 4.0) * cb, cb <= vec3<f32>(0.25));
+    let darkened = cb - (1.0 - 2.0 * cs) * cb * (1.0 - cb);
+    let lightened = cb + (2.0 * cs - 1.0) * (d - cb);
+    return select(lightened, darkened, cs <= vec3<f32>(0.5));
+}
+
+fn luminosity(c: vec3<f32>) -> f32 {
+    return dot(c, vec3<f32>(0.3, 0.59, 0.11));
+}
+
+fn saturation(c: vec3<f32>) -> f32 {
+    return max(max(c.r, c.g), c.b) - min(min(c.r, c.g), c.b);
+}
+
+fn clip_color(c: vec3<f32>) -> vec3<f32> {
+    let l = luminosity(c);
+    let n = min(min(c.r, c.g), c.b);
+    let x = max(max(c.r, c.g), c.b);
+    var clipped = c;
+    if (n < 0.0) {
+        clipped = l + (clipped - l) * l / (l - n);
+    }
+    if (x > 1.0) {
+        clipped = l + (clipped - l) * (1.0 - l) / (x - l);
+    }
+    return clipped;
+}
+
+fn set_luminosity(c: vec3<f32>, l: f32) -> vec3<f32> {
+    return clip_color(c + (l - luminosity(c)));
+}
+
+fn set_saturation(c: vec3<f32>, s: f32) -> vec3<f32> {
+    let n = min(min(c.r, c.g), c.b);
+    let x = max(max(c.r, c.g), c.b);
+    if (x > n) {
+        return (c - n) * s / (x - n);
+    }
+    return vec3<f32>(0.0);
+}
+
+// The blended color of straight backdrop and source colors
+fn blend(mode: u32, cb: vec3<f32>, cs: vec3<f32>) -> vec3<f32> {
+    if (mode == 1u) {
+        return cb * cs;
+    } else if (mode == 2u) {
+        return screen(cb, cs);
+    } else if (mode == 3u) {
+        return hard_light(cs, cb);
+    } else if (mode == 4u) {
+        return min(cb, cs);
+    } else if (mode == 5u) {
+        return max(cb, cs);
+    } else if (mode == 6u) {
+        return color_dodge(cb, cs);
+    } else if (mode == 7u) {
+        return color_burn(cb, cs);
+    } else if (mode == 8u) {
+        return hard_light(cb, cs);
+    } else if (mode == 9u) {
+        return soft_light(cb, cs);
+    } else if (mode == 10u) {
+        return abs(cb - cs);
+    } else if (mode == 11u) {
+        return cb + cs - 2.0 * cb * cs;
+    } else if (mode == 12u) {
+        return set_luminosity(set_saturation(cs, saturation(cb)), luminosity(cb));
+    } else if (mode == 13u) {
+        return set_luminosity(set_saturation(cb, saturation(cs)), luminosity(cb));
+    } else if (mode == 14u) {
+        return set_luminosity(cs, luminosity(cb));
+    } else if (mode == 15u) {
+        return set_luminosity(cb, luminosity(cs));
+    }
+    return cs;
+}
+
+fn unpremultiply(color: vec4<f32>) -> vec3<f32> {
+    if (color.a > 0.0) {
+        return color.rgb / color.a;
+    }
+    return vec3<f32>(0.0);
+}
+
+// Mixes the color with the backdrop under this pixel, then leaves source-over to the pipeline's blending,
+// so that partially covered samples are composited like with fs_main
+@fragment
+fn fs_blend(in: VertexOutput) -> @location(0) vec4<f32> {
+    let destination = textureLoad(backdrop, vec2<i32>(in.clip_position.xy), 0);
+    let source = unpremultiply(uniforms.color);
+    let mixed = source * (1.0 - destination.a)
+        + blend(uniforms.blend_mode, unpremultiply(destination), source) * destination.a;
+    return vec4<f32>(mixed * uniforms.color.a, uniforms.color.a);
+}
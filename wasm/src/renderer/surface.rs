@@ -0,0 +1,36 @@
+// Anything the renderer can draw to on screen: a canvas in the browser or a native window
+pub trait SurfaceSource {
+    // size of the drawing area in physical pixels
+    fn size(&self) -> (u32, u32);
+
+    fn create_surface(&self, instance: &wgpu::Instance) -> wgpu::Surface;
+}
+
+#[cfg(feature = "wasm")]
+impl SurfaceSource for web_sys::HtmlCanvasElement {
+    fn size(&self) -> (u32, u32) {
+        (self.width(), self.height())
+    }
+
+    fn create_surface(&self, instance: &wgpu::Instance) -> wgpu::Surface {
+        instance.create_surface_from_canvas(self)
+    }
+}
+
+// A native window (e.g. from winit) together with its inner size in physical pixels
+pub struct Window<'a, W> {
+    pub window: &'a W,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl<'a, W: raw_window_handle::HasRawWindowHandle> SurfaceSource for Window<'a, W> {
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn create_surface(&self, instance: &wgpu::Instance) -> wgpu::Surface {
+        // the window outlives the renderer's use of it as long as the caller keeps it open
+        unsafe { instance.create_surface(self.window) }
+    }
+}
@@ -0,0 +1,72 @@
+use cgmath::{point2, Vector4};
+use lyon::lyon_tessellation::{
+    self, BuffersBuilder, FillOptions, FillTessellator, FillVertex, TessellationError,
+    VertexBuffers,
+};
+use lyon::math::point;
+
+use super::{mesh::Vertex, scene::Shape};
+use crate::geometry::{FillRule, Path, Rect};
+
+// Triangles covering the filled region of the path.
+// Fails if the path needs more vertices than 16-bit indices can address.
+pub fn tessellate_fill(
+    path: &Path,
+    fill_rule: FillRule,
+) -> Result<VertexBuffers<Vertex, u16>, TessellationError> {
+    let to_lyon = |p: cgmath::Point2<f64>| point(p.x as f32, p.y as f32);
+    let mut builder = lyon::path::Path::builder();
+    for subpath in path.subpaths() {
+        builder.begin(to_lyon(subpath.start()));
+        for segment in &subpath.segments {
+            let points = segment.points();
+            match points[..] {
+                [_, p1] => builder.line_to(to_lyon(p1)),
+                [_, p1, p2] => builder.quadratic_bezier_to(to_lyon(p1), to_lyon(p2)),
+                [_, p1, p2, p3] => builder.cubic_bezier_to(to_lyon(p1), to_lyon(p2), to_lyon(p3)),
+                _ => unreachable!(),
+            };
+        }
+        // filled subpaths are implicitly closed
+        builder.end(true);
+    }
+    let lyon_path = builder.build();
+
+    let options = FillOptions::default().with_fill_rule(match fill_rule {
+        FillRule::NonZero => lyon_tessellation::FillRule::NonZero,
+        FillRule::EvenOdd => lyon_tessellation::FillRule::EvenOdd,
+    });
+    let mut geometry: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        &lyon_path,
+        &options,
+        &mut BuffersBuilder::new(&mut geometry, |vertex: FillVertex| Vertex {
+            position: point2(vertex.position().x, vertex.position().y),
+        }),
+    )?;
+    Ok(geometry)
+}
+
+impl Shape {
+    // The filled path in scene coordinates
+    pub fn from_path(
+        path: &Path,
+        fill_rule: FillRule,
+        color: Vector4<f32>,
+    ) -> Result<Shape, TessellationError> {
+        let mut shape = Shape::new(tessellate_fill(path, fill_rule)?, color);
+        shape.path = Some((path.clone(), fill_rule));
+        Ok(shape)
+    }
+
+    // The part of the filled path inside `clip`, e.g. the visible area.
+    // The rest is cut away before tessellating, so it costs no vertices.
+    pub fn from_clipped_path(
+        path: &Path,
+        fill_rule: FillRule,
+        color: Vector4<f32>,
+        clip: &Rect,
+    ) -> Result<Shape, TessellationError> {
+        Shape::from_path(&path.clip_to_rect(clip), fill_rule, color)
+    }
+}
@@ -1,28 +1,44 @@
 use crate::util::cast_to_u8_slice;
-use log::info;
-use wgpu::util::DeviceExt;
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug)]
 pub struct UniformsValue {
     pub view_proj: cgmath::Matrix4<f32>,
+    // premultiplied RGBA, as blended by the pipeline
     pub color: cgmath::Vector4<f32>,
+    // `BlendMode`, only read by the pipeline for modes other than normal
+    pub blend_mode: u32,
 }
 
+// Size of the struct in WGSL, which rounds it up to the alignment of its matrix
+const BINDING_SIZE: u64 = (std::mem::size_of::<UniformsValue>() as u64).div_ceil(16) * 16;
+
+impl UniformsValue {
+    // Maps pixel coordinates (origin top left, y down) of a viewport to clip space
+    pub fn pixel_projection(width: f32, height: f32) -> cgmath::Matrix4<f32> {
+        [
+            [2.0 / width, 0.0, 0.0, 0.0],
+            [0.0, -2.0 / height, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [-1.0, 1.0, 0.0, 1.0],
+        ]
+        .into()
+    }
+}
+
+// Uniforms of all shapes in one buffer, each bound with a dynamic offset
 pub struct Uniforms {
     pub buffer: wgpu::Buffer,
     pub bind_group_layout: wgpu::BindGroupLayout,
     pub bind_group: wgpu::BindGroup,
+    // number of values the buffer has room for
+    capacity: usize,
+    // distance between values, a multiple of the device's offset alignment
+    stride: usize,
 }
 
 impl Uniforms {
-    pub fn new(device: &wgpu::Device, value: UniformsValue) -> Uniforms {
-        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Uniforms Buffer"),
-            contents: cast_to_u8_slice(&[value]),
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-        });
-
+    pub fn new(device: &wgpu::Device) -> Uniforms {
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("Uniforms Bind Group Layout"),
             entries: &[wgpu::BindGroupLayoutEntry {
@@ -30,26 +46,90 @@ impl Uniforms {
                 visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
                 ty: wgpu::BindingType::Buffer {
                     ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
+                    has_dynamic_offset: true,
+                    min_binding_size: wgpu::BufferSize::new(BINDING_SIZE),
                 },
                 count: None,
             }],
         });
 
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Uniforms Bind Group"),
-            layout: &bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: buffer.as_entire_binding(),
-            }],
-        });
+        let alignment = device.limits().min_uniform_buffer_offset_alignment as usize;
+        let stride = (BINDING_SIZE as usize).div_ceil(alignment) * alignment;
+        let capacity = 16;
+        let (buffer, bind_group) = create_buffer(device, &bind_group_layout, capacity, stride);
 
-        return Uniforms {
+        Uniforms {
             buffer,
             bind_group_layout,
             bind_group,
-        };
+            capacity,
+            stride,
+        }
+    }
+
+    // Dynamic offset of the value at the index
+    pub fn offset(&self, index: usize) -> wgpu::DynamicOffset {
+        (index * self.stride) as wgpu::DynamicOffset
+    }
+
+    // Uploads the values, growing the buffer if needed (which replaces the bind group)
+    pub fn write(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, values: &[UniformsValue]) {
+        if values.len() > self.capacity {
+            self.capacity = values.len().next_power_of_two();
+            (self.buffer, self.bind_group) =
+                create_buffer(device, &self.bind_group_layout, self.capacity, self.stride);
+        }
+
+        let mut contents = vec![0u8; values.len() * self.stride];
+        for (value, slot) in values.iter().zip(contents.chunks_mut(self.stride)) {
+            let bytes = cast_to_u8_slice(std::slice::from_ref(value));
+            slot[..bytes.len()].copy_from_slice(bytes);
+        }
+        if !contents.is_empty() {
+            queue.write_buffer(&self.buffer, 0, &contents);
+        }
+    }
+
+    // Uploads a single value into a slot the buffer already has room for
+    pub fn write_at(&self, queue: &wgpu::Queue, index: usize, value: UniformsValue) {
+        assert!(
+            index < self.capacity,
+            "uniforms index {} out of range",
+            index
+        );
+        queue.write_buffer(
+            &self.buffer,
+            self.offset(index) as wgpu::BufferAddress,
+            cast_to_u8_slice(std::slice::from_ref(&value)),
+        );
     }
 }
+
+fn create_buffer(
+    device: &wgpu::Device,
+    bind_group_layout: &wgpu::BindGroupLayout,
+    capacity: usize,
+    stride: usize,
+) -> (wgpu::Buffer, wgpu::BindGroup) {
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Uniforms Buffer"),
+        size: (capacity * stride) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Uniforms Bind Group"),
+        layout: bind_group_layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer: &buffer,
+                offset: 0,
+                size: wgpu::BufferSize::new(BINDING_SIZE),
+            }),
+        }],
+    });
+
+    (buffer, bind_group)
+}
@@ -1,8 +1,43 @@
+// Straight RGBA to premultiplied RGBA
+pub fn premultiply(color: cgmath::Vector4<f32>) -> cgmath::Vector4<f32> {
+    cgmath::vec4(
+        color.x * color.w,
+        color.y * color.w,
+        color.z * color.w,
+        color.w,
+    )
+}
+
 pub fn cast_to_u8_slice<T>(xs: &[T]) -> &[u8] {
-    unsafe {
-        std::slice::from_raw_parts(
-            xs.as_ptr() as *const u8,
-            xs.len() * std::mem::size_of::<T>(),
-        )
+    unsafe { std::slice::from_raw_parts(xs.as_ptr() as *const u8, std::mem::size_of_val(xs)) }
+}
+
+// Runs a future on the current thread, for the native examples and tests.
+// wgpu's futures are woken by its own polling, so parking until woken is enough.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn block_on<T>(future: impl std::future::Future<Output = T>) -> T {
+    use std::{
+        pin::pin,
+        sync::Arc,
+        task::{Context, Poll, Wake},
+        thread::{self, Thread},
+    };
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let mut future = pin!(future);
+    let waker = Arc::new(ThreadWaker(thread::current())).into();
+    let mut context = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(value) => return value,
+            Poll::Pending => thread::park(),
+        }
     }
 }
@@ -0,0 +1,179 @@
+// Shapes with blend modes mix with what's beneath them before source-over compositing,
+// in the CPU rasterizer and in the GPU renderer's backdrop-reading passes.
+
+mod common;
+
+use cgmath::{vec3, vec4, Vector4};
+use common::{block_on, rgba8_pixel};
+use wasm::{
+    geometry::{FillRule, Path},
+    raster::{blend, Pixmap},
+    renderer::{BlendMode, Renderer, Shape},
+};
+
+const MODES: [BlendMode; 16] = [
+    BlendMode::Normal,
+    BlendMode::Multiply,
+    BlendMode::Screen,
+    BlendMode::Overlay,
+    BlendMode::Darken,
+    BlendMode::Lighten,
+    BlendMode::ColorDodge,
+    BlendMode::ColorBurn,
+    BlendMode::HardLight,
+    BlendMode::SoftLight,
+    BlendMode::Difference,
+    BlendMode::Exclusion,
+    BlendMode::Hue,
+    BlendMode::Saturation,
+    BlendMode::Color,
+    BlendMode::Luminosity,
+];
+
+// channels on both sides of 0.5 and 0.25, for the modes that switch formulas there
+const BACKDROP: Vector4<f32> = vec4(0.9, 0.2, 0.6, 1.0);
+const SOURCE: Vector4<f32> = vec4(0.3, 0.8, 0.1, 0.75);
+
+fn square(x: f64, y: f64, size: f64) -> Path {
+    Path::from_svg(&format!("M{} {} h{} v{} h{} z", x, y, size, size, -size)).unwrap()
+}
+
+fn assert_close(actual: Vector4<f32>, expected: Vector4<f32>, tolerance: f32, context: &str) {
+    for channel in 0..4 {
+        assert!(
+            (actual[channel] - expected[channel]).abs() <= tolerance,
+            "{}: expected {:?}, got {:?}",
+            context,
+            expected,
+            actual
+        );
+    }
+}
+
+#[test]
+fn separable_modes_over_opaque_backdrop() {
+    for (mode, blended) in [
+        (BlendMode::Multiply, vec3(0.27, 0.16, 0.06)),
+        (BlendMode::Screen, vec3(0.93, 0.84, 0.64)),
+        (BlendMode::Darken, vec3(0.3, 0.2, 0.1)),
+        (BlendMode::Lighten, vec3(0.9, 0.8, 0.6)),
+        (BlendMode::Difference, vec3(0.6, 0.6, 0.5)),
+        // 0.9 + 0.3 - 2 * 0.27, ...
+        (BlendMode::Exclusion, vec3(0.66, 0.68, 0.58)),
+        // multiply(0.9, 0.6), screen(0.2, 0.6), multiply(0.6, 0.2)
+        (BlendMode::HardLight, vec3(0.54, 0.68, 0.12)),
+        // hard light with the colors swapped
+        (BlendMode::Overlay, vec3(0.86, 0.32, 0.28)),
+    ] {
+        let mut pixmap = Pixmap::new(2, 2);
+        pixmap.clear(BACKDROP);
+        pixmap.fill_path_blended(&square(0.0, 0.0, 2.0), FillRule::NonZero, SOURCE, mode);
+
+        // opaque backdrop: the blended color replaces the source color, then source-over
+        let mixed = blended * SOURCE.w + BACKDROP.truncate() * (1.0 - SOURCE.w);
+        let expected = vec4(mixed.x, mixed.y, mixed.z, 1.0);
+        assert_close(pixmap.pixel(1, 1), expected, 1e-5, &format!("{:?}", mode));
+    }
+}
+
+#[test]
+fn non_separable_modes_keep_luminosity() {
+    let luminosity = |c: cgmath::Vector3<f32>| 0.3 * c.x + 0.59 * c.y + 0.11 * c.z;
+    let (backdrop, source) = (BACKDROP.truncate(), SOURCE.truncate());
+
+    let color = blend(BlendMode::Color, backdrop, source);
+    assert!((luminosity(color) - luminosity(backdrop)).abs() < 1e-5);
+    let luminosity_mode = blend(BlendMode::Luminosity, backdrop, source);
+    assert!((luminosity(luminosity_mode) - luminosity(source)).abs() < 1e-5);
+
+    // gray has no hue or saturation to give
+    let gray = vec3(0.5, 0.5, 0.5);
+    let l = luminosity(backdrop);
+    assert_close(
+        blend(BlendMode::Saturation, backdrop, gray).extend(1.0),
+        vec4(l, l, l, 1.0),
+        1e-5,
+        "saturation",
+    );
+    for mode in MODES {
+        let blended = blend(mode, backdrop, source);
+        for channel in 0..3 {
+            assert!(
+                (-1e-5..=1.0 + 1e-5).contains(&blended[channel]),
+                "{:?} gives {:?}",
+                mode,
+                blended
+            );
+        }
+    }
+}
+
+#[test]
+fn blending_with_transparent_backdrop_is_source_over() {
+    for mode in MODES {
+        let mut pixmap = Pixmap::new(2, 2);
+        pixmap.fill_path_blended(&square(0.0, 0.0, 2.0), FillRule::NonZero, SOURCE, mode);
+        assert_close(
+            pixmap.pixel(0, 0),
+            vec4(0.225, 0.6, 0.075, 0.75),
+            1e-6,
+            &format!("{:?}", mode),
+        );
+    }
+}
+
+#[test]
+fn gpu_blends_like_the_rasterizer() {
+    let (width, height) = (40, 40);
+    let results = block_on(async {
+        let mut renderer =
+            Renderer::new_headless(width, height, wgpu::TextureFormat::Rgba8Unorm).await;
+        let mut results = Vec::new();
+        for mode in MODES {
+            renderer.clear_shapes();
+            // a blended shape first, so it reads the clear color
+            let first = renderer.add_shape(
+                Shape::from_path(&square(0.0, 0.0, 25.0), FillRule::NonZero, BACKDROP).unwrap(),
+            );
+            renderer.set_blend_mode(first, mode);
+            renderer.add_shape(
+                Shape::from_path(
+                    &square(5.0, 5.0, 10.0),
+                    FillRule::NonZero,
+                    vec4(0.1, 0.4, 0.9, 0.5),
+                )
+                .unwrap(),
+            );
+            // two blended shapes in a row read each other's result
+            for offset in [15.0, 20.0] {
+                let id = renderer.add_shape(
+                    Shape::from_path(&square(offset, offset, 15.0), FillRule::NonZero, SOURCE)
+                        .unwrap(),
+                );
+                renderer.set_blend_mode(id, mode);
+            }
+            renderer.render();
+
+            let mut pixmap = Pixmap::new(width as usize, height as usize);
+            pixmap.render_scene(renderer.scene());
+            results.push((
+                mode,
+                renderer.read_pixels().await.unwrap(),
+                pixmap.to_rgba8(),
+            ));
+        }
+        results
+    });
+
+    let tolerance = 3.0 / 255.0;
+    for (mode, gpu, cpu) in results {
+        for (x, y) in [(2, 2), (10, 10), (17, 17), (22, 22), (32, 32), (37, 2)] {
+            assert_close(
+                rgba8_pixel(&gpu, width as usize, x, y),
+                rgba8_pixel(&cpu, width as usize, x, y),
+                tolerance,
+                &format!("{:?} at ({}, {})", mode, x, y),
+            );
+        }
+    }
+}
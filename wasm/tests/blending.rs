@@ -0,0 +1,97 @@
+// Overlapping translucent shapes composite with premultiplied source-over,
+// both in the CPU rasterizer and in the GPU renderer.
+
+mod common;
+
+use cgmath::{vec4, Vector4};
+use common::{block_on, rgba8_pixel};
+use wasm::{
+    geometry::{FillRule, Path},
+    raster::Pixmap,
+    renderer::{Renderer, Shape},
+};
+
+const RED: Vector4<f32> = vec4(1.0, 0.0, 0.0, 0.5);
+const BLUE: Vector4<f32> = vec4(0.0, 0.0, 1.0, 0.5);
+
+fn square(x: f64, y: f64, size: f64) -> Path {
+    Path::from_svg(&format!("M{} {} h{} v{} h{} z", x, y, size, size, -size)).unwrap()
+}
+
+fn assert_close(actual: Vector4<f32>, expected: Vector4<f32>, tolerance: f32) {
+    for channel in 0..4 {
+        assert!(
+            (actual[channel] - expected[channel]).abs() <= tolerance,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+}
+
+#[test]
+fn translucent_shapes_over_opaque_background() {
+    let mut pixmap = Pixmap::new(40, 40);
+    pixmap.clear(vec4(1.0, 1.0, 1.0, 1.0));
+    pixmap.fill_path(&square(0.0, 0.0, 20.0), FillRule::NonZero, RED);
+    pixmap.fill_path(&square(10.0, 10.0, 20.0), FillRule::NonZero, BLUE);
+
+    assert_close(pixmap.pixel(5, 5), vec4(1.0, 0.5, 0.5, 1.0), 1e-6);
+    assert_close(pixmap.pixel(25, 25), vec4(0.5, 0.5, 1.0, 1.0), 1e-6);
+    // blue over (red over white)
+    assert_close(pixmap.pixel(15, 15), vec4(0.5, 0.25, 0.75, 1.0), 1e-6);
+    assert_close(pixmap.pixel(35, 5), vec4(1.0, 1.0, 1.0, 1.0), 1e-6);
+}
+
+#[test]
+fn translucent_shapes_over_transparent_background() {
+    let mut pixmap = Pixmap::new(40, 40);
+    pixmap.fill_path(&square(0.0, 0.0, 20.0), FillRule::NonZero, RED);
+    pixmap.fill_path(&square(10.0, 10.0, 20.0), FillRule::NonZero, BLUE);
+
+    // premultiplied, so the color channels never exceed alpha
+    assert_close(pixmap.pixel(5, 5), vec4(0.5, 0.0, 0.0, 0.5), 1e-6);
+    assert_close(pixmap.pixel(15, 15), vec4(0.25, 0.0, 0.5, 0.75), 1e-6);
+    assert_close(pixmap.pixel(35, 5), vec4(0.0, 0.0, 0.0, 0.0), 1e-6);
+}
+
+#[test]
+fn partial_coverage_scales_premultiplied_color() {
+    let mut pixmap = Pixmap::new(4, 4);
+    // covers the left half of the pixels in column 1
+    pixmap.fill_path(&square(0.0, 0.0, 1.5), FillRule::NonZero, RED);
+
+    assert_close(pixmap.pixel(0, 0), vec4(0.5, 0.0, 0.0, 0.5), 1e-6);
+    assert_close(pixmap.pixel(1, 0), vec4(0.25, 0.0, 0.0, 0.25), 1e-6);
+}
+
+#[test]
+fn gpu_composites_like_the_rasterizer() {
+    let (width, height) = (40, 40);
+    let (gpu, cpu) = block_on(async {
+        let mut renderer =
+            Renderer::new_headless(width, height, wgpu::TextureFormat::Rgba8Unorm).await;
+        renderer.clear_shapes();
+        renderer
+            .add_shape(Shape::from_path(&square(0.0, 0.0, 20.0), FillRule::NonZero, RED).unwrap());
+        renderer.add_shape(
+            Shape::from_path(&square(10.0, 10.0, 20.0), FillRule::NonZero, BLUE).unwrap(),
+        );
+        renderer.render();
+
+        let mut pixmap = Pixmap::new(width as usize, height as usize);
+        pixmap.render_scene(renderer.scene());
+        (renderer.read_pixels().await.unwrap(), pixmap.to_rgba8())
+    });
+
+    let tolerance = 2.0 / 255.0;
+    for (x, y, expected) in [
+        (5, 5, vec4(1.0, 0.5, 0.5, 1.0)),
+        (15, 15, vec4(0.5, 0.25, 0.75, 1.0)),
+        (25, 25, vec4(0.5, 0.5, 1.0, 1.0)),
+        (35, 5, vec4(1.0, 1.0, 1.0, 1.0)),
+    ] {
+        assert_close(rgba8_pixel(&gpu, width as usize, x, y), expected, tolerance);
+        assert_close(rgba8_pixel(&cpu, width as usize, x, y), expected, tolerance);
+    }
+}
@@ -0,0 +1,28 @@
+// Shapes clipped to a rect only tessellate the part of the path inside it
+
+use cgmath::{point2, vec4};
+use wasm::{
+    geometry::{FillRule, Path, Rect},
+    renderer::Shape,
+};
+
+#[test]
+fn clipped_shapes_only_have_vertices_inside_the_clip_rect() {
+    let path = Path::from_svg("M0 0 H100 V100 H0 Z M200 0 H300 V100 H200 Z").unwrap();
+    let color = vec4(0.0, 0.0, 0.0, 1.0);
+    let clip = Rect::new(point2(50.0, 25.0), point2(150.0, 75.0));
+
+    let shape = Shape::from_clipped_path(&path, FillRule::NonZero, color, &clip).unwrap();
+    let full = Shape::from_path(&path, FillRule::NonZero, color).unwrap();
+    assert!(!shape.geometry.indices.is_empty());
+    assert!(shape.geometry.vertices.len() < full.geometry.vertices.len());
+    for vertex in &shape.geometry.vertices {
+        let position = vertex.position;
+        assert!((50.0..=100.0).contains(&position.x) && (25.0..=75.0).contains(&position.y));
+    }
+
+    // nothing visible, nothing to draw
+    let outside = Rect::new(point2(120.0, 0.0), point2(180.0, 100.0));
+    let shape = Shape::from_clipped_path(&path, FillRule::NonZero, color, &outside).unwrap();
+    assert!(shape.geometry.vertices.is_empty() && shape.geometry.indices.is_empty());
+}
@@ -0,0 +1,11 @@
+// Helpers shared by the integration tests
+
+use cgmath::{vec4, Vector4};
+
+pub use wasm::block_on;
+
+// The pixel at (x, y) of `Renderer::read_pixels`/`Pixmap::to_rgba8` rows, as 0..1 channels
+pub fn rgba8_pixel(pixels: &[u8], width: usize, x: usize, y: usize) -> Vector4<f32> {
+    let i = (y * width + x) * 4;
+    vec4(pixels[i], pixels[i + 1], pixels[i + 2], pixels[i + 3]).map(|c| c as f32 / 255.0)
+}
@@ -0,0 +1,195 @@
+// A headless renderer starts from an empty scene and reads back what it drew.
+
+mod common;
+
+use cgmath::{vec3, vec4, Matrix4};
+use common::{block_on, rgba8_pixel};
+use wasm::{
+    geometry::{FillRule, Path},
+    raster::Pixmap,
+    renderer::{Renderer, Shape, ShapeId},
+};
+
+#[test]
+fn starts_with_an_empty_scene() {
+    let (width, height) = (32, 32);
+    let pixels = block_on(async {
+        let mut renderer =
+            Renderer::new_headless(width, height, wgpu::TextureFormat::Rgba8Unorm).await;
+        assert_eq!(renderer.scene().shapes().count(), 0);
+        renderer.render();
+        renderer.read_pixels().await.unwrap()
+    });
+
+    assert_eq!(pixels.len(), (width * height * 4) as usize);
+    assert!(pixels.chunks(4).all(|pixel| pixel == [255, 255, 255, 255]));
+}
+
+#[test]
+fn read_pixels_returns_added_shapes() {
+    let (width, height) = (32, 32);
+    let pixels = block_on(async {
+        let mut renderer =
+            Renderer::new_headless(width, height, wgpu::TextureFormat::Rgba8Unorm).await;
+        let square = Path::from_svg("M0 0 h16 v16 h-16 z").unwrap();
+        renderer.add_shape(
+            Shape::from_path(&square, FillRule::NonZero, vec4(1.0, 0.0, 0.0, 1.0)).unwrap(),
+        );
+        renderer.render();
+        renderer.read_pixels().await.unwrap()
+    });
+
+    assert_eq!(
+        rgba8_pixel(&pixels, width as usize, 8, 8),
+        vec4(1.0, 0.0, 0.0, 1.0)
+    );
+    assert_eq!(
+        rgba8_pixel(&pixels, width as usize, 24, 24),
+        vec4(1.0, 1.0, 1.0, 1.0)
+    );
+}
+
+#[test]
+fn set_fill_color_applies_to_the_next_frame() {
+    let (width, height) = (32, 32);
+    let (before, after) = block_on(async {
+        let mut renderer =
+            Renderer::new_headless(width, height, wgpu::TextureFormat::Rgba8Unorm).await;
+        let square = Path::from_svg("M0 0 h16 v16 h-16 z").unwrap();
+        let id = renderer.add_shape(
+            Shape::from_path(&square, FillRule::NonZero, vec4(1.0, 0.0, 0.0, 1.0)).unwrap(),
+        );
+        renderer.render();
+        let before = renderer.read_pixels().await.unwrap();
+
+        // only the shape's uniforms are rewritten, its mesh stays the same
+        assert!(renderer.set_fill_color(id, vec4(0.0, 0.0, 1.0, 1.0)));
+        renderer.render();
+        (before, renderer.read_pixels().await.unwrap())
+    });
+
+    assert_eq!(
+        rgba8_pixel(&before, width as usize, 8, 8),
+        vec4(1.0, 0.0, 0.0, 1.0)
+    );
+    assert_eq!(
+        rgba8_pixel(&after, width as usize, 8, 8),
+        vec4(0.0, 0.0, 1.0, 1.0)
+    );
+    assert_eq!(
+        rgba8_pixel(&after, width as usize, 24, 24),
+        vec4(1.0, 1.0, 1.0, 1.0)
+    );
+}
+
+#[test]
+fn mirrored_shapes_are_drawn() {
+    let (width, height) = (32, 32);
+    let (pixels, expected) = block_on(async {
+        let mut renderer =
+            Renderer::new_headless(width, height, wgpu::TextureFormat::Rgba8Unorm).await;
+        let square = Path::from_svg("M0 0 h16 v16 h-16 z").unwrap();
+        let mut shape =
+            Shape::from_path(&square, FillRule::NonZero, vec4(1.0, 0.0, 0.0, 1.0)).unwrap();
+        // flips the square onto the right half, which reverses its winding
+        shape.transform = Matrix4::from_translation(vec3(32.0, 0.0, 0.0))
+            * Matrix4::from_nonuniform_scale(-1.0, 1.0, 1.0);
+        renderer.add_shape(shape);
+        renderer.render();
+
+        let mut pixmap = Pixmap::new(width as usize, height as usize);
+        pixmap.render_scene(renderer.scene());
+        (renderer.read_pixels().await.unwrap(), pixmap.to_rgba8())
+    });
+
+    assert_eq!(
+        rgba8_pixel(&pixels, width as usize, 24, 8),
+        vec4(1.0, 0.0, 0.0, 1.0)
+    );
+    assert_eq!(
+        rgba8_pixel(&pixels, width as usize, 8, 8),
+        vec4(1.0, 1.0, 1.0, 1.0)
+    );
+    assert_eq!(
+        rgba8_pixel(&expected, width as usize, 24, 8),
+        vec4(1.0, 0.0, 0.0, 1.0)
+    );
+    assert_eq!(
+        rgba8_pixel(&expected, width as usize, 8, 8),
+        vec4(1.0, 1.0, 1.0, 1.0)
+    );
+}
+
+#[test]
+fn panning_reveals_parts_clipped_away_before() {
+    let (width, height) = (32, 32);
+    let (pixels, expected) = block_on(async {
+        let mut renderer =
+            Renderer::new_headless(width, height, wgpu::TextureFormat::Rgba8Unorm).await;
+        // the second square is far enough off-screen to be clipped away at first
+        let squares = Path::from_svg("M0 0 h16 v16 h-16 z M200 200 h16 v16 h-16 z").unwrap();
+        renderer.add_shape(
+            Shape::from_path(&squares, FillRule::NonZero, vec4(1.0, 0.0, 0.0, 1.0)).unwrap(),
+        );
+        renderer.render();
+
+        renderer.set_view_transform(-200.0, -200.0, 1.0, 0.0);
+        renderer.render();
+        let mut pixmap = Pixmap::new(width as usize, height as usize);
+        pixmap.render_scene(renderer.scene());
+        (renderer.read_pixels().await.unwrap(), pixmap.to_rgba8())
+    });
+
+    assert_eq!(pixels, expected);
+    assert_eq!(
+        rgba8_pixel(&pixels, width as usize, 8, 8),
+        vec4(1.0, 0.0, 0.0, 1.0)
+    );
+    assert_eq!(
+        rgba8_pixel(&pixels, width as usize, 24, 24),
+        vec4(1.0, 1.0, 1.0, 1.0)
+    );
+}
+
+#[test]
+fn resize_keeps_the_layout_in_css_pixels() {
+    let pixels = block_on(async {
+        let mut renderer = Renderer::new_headless(32, 32, wgpu::TextureFormat::Rgba8Unorm).await;
+        let square = Path::from_svg("M0 0 h16 v16 h-16 z").unwrap();
+        renderer.add_shape(
+            Shape::from_path(&square, FillRule::NonZero, vec4(1.0, 0.0, 0.0, 1.0)).unwrap(),
+        );
+        renderer.resize(32, 32, 2.0);
+        assert_eq!(renderer.device_pixel_ratio(), 2.0);
+        renderer.render();
+        renderer.read_pixels().await.unwrap()
+    });
+
+    // twice as many physical pixels each way, the square still covers a quarter
+    assert_eq!(pixels.len(), 64 * 64 * 4);
+    assert_eq!(rgba8_pixel(&pixels, 64, 30, 30), vec4(1.0, 0.0, 0.0, 1.0));
+    assert_eq!(rgba8_pixel(&pixels, 64, 34, 34), vec4(1.0, 1.0, 1.0, 1.0));
+}
+
+#[test]
+fn remove_shape_only_removes_existing_shapes() {
+    let (width, height) = (32, 32);
+    let pixels = block_on(async {
+        let mut renderer =
+            Renderer::new_headless(width, height, wgpu::TextureFormat::Rgba8Unorm).await;
+        let square = Path::from_svg("M0 0 h16 v16 h-16 z").unwrap();
+        let id = renderer.add_shape(
+            Shape::from_path(&square, FillRule::NonZero, vec4(1.0, 0.0, 0.0, 1.0)).unwrap(),
+        );
+        renderer.render();
+
+        assert!(!renderer.remove_shape(ShapeId(id.0 + 1)));
+        assert!(renderer.remove_shape(id));
+        assert!(!renderer.remove_shape(id));
+        assert_eq!(renderer.scene().shapes().count(), 0);
+        renderer.render();
+        renderer.read_pixels().await.unwrap()
+    });
+
+    assert!(pixels.chunks(4).all(|pixel| pixel == [255, 255, 255, 255]));
+}
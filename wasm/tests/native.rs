@@ -0,0 +1,54 @@
+// Without the wasm feature the renderer is a plain native library: it draws the example scene
+// offscreen and takes any window with a raw handle as a surface.
+
+mod common;
+
+use cgmath::vec4;
+use common::{block_on, rgba8_pixel};
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle, XlibHandle};
+use wasm::renderer::{Renderer, Scene, SurfaceSource, Window};
+
+#[test]
+fn renders_the_example_scene_offscreen() {
+    let (width, height) = (320, 160);
+    let pixels = block_on(async {
+        let mut renderer =
+            Renderer::new_headless(width, height, wgpu::TextureFormat::Rgba8Unorm).await;
+        for (_, shape) in Scene::example(width, height).shapes() {
+            renderer.add_shape(shape.clone());
+        }
+        renderer.render();
+        renderer.read_pixels().await.unwrap()
+    });
+
+    assert_eq!(pixels.len(), (width * height * 4) as usize);
+    assert_eq!(
+        rgba8_pixel(&pixels, width as usize, 0, 0),
+        vec4(1.0, 1.0, 1.0, 1.0)
+    );
+    // the text is drawn somewhere
+    assert!(pixels.chunks(4).any(|pixel| pixel != [255, 255, 255, 255]));
+}
+
+// stands in for a winit window; its handle is never used to create a surface here
+struct FakeWindow;
+
+unsafe impl HasRawWindowHandle for FakeWindow {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        RawWindowHandle::Xlib(XlibHandle::empty())
+    }
+}
+
+fn size_of_surface(source: &impl SurfaceSource) -> (u32, u32) {
+    source.size()
+}
+
+#[test]
+fn windows_are_surface_sources() {
+    let window = Window {
+        window: &FakeWindow,
+        width: 640,
+        height: 480,
+    };
+    assert_eq!(size_of_surface(&window), (640, 480));
+}